@@ -1,3 +1,6 @@
 fn main() {
-    embuild::espidf::sysenv::output();
+    //esp-idf's link args only mean something when building the firmware
+    if std::env::var("CARGO_CFG_TARGET_OS").as_deref() == Ok("espidf") {
+        embuild::espidf::sysenv::output();
+    }
 }
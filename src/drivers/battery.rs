@@ -0,0 +1,106 @@
+use crate::system::events::{Event, EventQueue};
+use esp_idf_hal::adc::attenuation::DB_11;
+use esp_idf_hal::adc::oneshot::config::{AdcChannelConfig, Calibration};
+use esp_idf_hal::adc::oneshot::{AdcChannelDriver, AdcDriver};
+use esp_idf_hal::adc::ADCPin;
+use esp_idf_hal::peripheral::Peripheral;
+use esp_idf_sys::EspError;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy)]
+pub struct BatteryConfig {
+    pub min_mv: u32,
+    pub max_mv: u32,
+    pub divider_ratio: f32,
+    pub low_threshold: u8,
+    pub samples: u8,
+}
+
+impl Default for BatteryConfig {
+    fn default() -> Self {
+        //single cell lipo behind a 1:1 divider
+        Self {
+            min_mv: 3300,
+            max_mv: 4200,
+            divider_ratio: 2.0,
+            low_threshold: 15,
+            samples: 8,
+        }
+    }
+}
+
+pub struct BatteryMonitor<'d, T: ADCPin> {
+    channel: AdcChannelDriver<'d, T, AdcDriver<'d, T::Adc>>,
+    config: BatteryConfig,
+    event_queue: Arc<EventQueue>,
+    last_percentage: u8,
+    low_reported: bool,
+}
+
+impl<'d, T: ADCPin> BatteryMonitor<'d, T> {
+    pub fn new(
+        adc: AdcDriver<'d, T::Adc>,
+        pin: impl Peripheral<P = T> + 'd,
+        config: BatteryConfig,
+        event_queue: Arc<EventQueue>,
+    ) -> Result<Self, EspError> {
+        let channel_config = AdcChannelConfig {
+            attenuation: DB_11,
+            calibration: Calibration::Line,
+            ..Default::default()
+        };
+
+        Ok(Self {
+            channel: AdcChannelDriver::new(adc, pin, &channel_config)?,
+            config,
+            event_queue,
+            last_percentage: 100,
+            low_reported: false,
+        })
+    }
+
+    pub fn read_millivolts(&mut self) -> Result<u32, EspError> {
+        let samples = self.config.samples.max(1) as u32;
+        let mut total = 0u32;
+
+        //single adc reads jitter by tens of mV, average them out
+        for _ in 0..samples {
+            total += self.channel.read()? as u32;
+        }
+
+        Ok(((total / samples) as f32 * self.config.divider_ratio) as u32)
+    }
+
+    pub fn read_percentage(&mut self) -> u8 {
+        let millivolts = match self.read_millivolts() {
+            Ok(mv) => mv,
+            Err(e) => {
+                log::error!("Battery read failed: {}", e);
+                return self.last_percentage;
+            }
+        };
+
+        let range = self.config.max_mv.saturating_sub(self.config.min_mv).max(1);
+        let above_min = millivolts.saturating_sub(self.config.min_mv).min(range);
+        let percentage = (above_min * 100 / range) as u8;
+
+        if percentage != self.last_percentage {
+            self.event_queue.push_coalesced(Event::BatteryLevel(percentage));
+        }
+
+        self.last_percentage = percentage;
+        self.check_low(percentage);
+
+        percentage
+    }
+
+    fn check_low(&mut self, percentage: u8) {
+        //report once per dip, re-arm only after a few percent of recovery so noise can't spam it
+        if percentage < self.config.low_threshold && !self.low_reported {
+            self.event_queue.push(Event::BatteryLow(percentage));
+            self.low_reported = true;
+        } else if percentage >= self.config.low_threshold.saturating_add(5) {
+            self.low_reported = false;
+        }
+    }
+}
@@ -1,26 +1,36 @@
 use embedded_graphics::{
-    mono_font::{ascii::FONT_6X10, MonoTextStyleBuilder},
+    image::{Image, ImageRaw},
+    mono_font::{ascii::{FONT_5X8, FONT_6X10, FONT_9X15}, MonoFont, MonoTextStyleBuilder},
     pixelcolor::BinaryColor,
     prelude::*,
-    primitives::{Line, PrimitiveStyle, Rectangle},
+    primitives::{Circle, CornerRadii, Line, PrimitiveStyle, Rectangle, RoundedRectangle},
     text::{Baseline, Text},
 };
 
+//the ssd1306 itself is only driven on the esp32, host builds draw into a Framebuffer
+#[cfg(target_os = "espidf")]
 use esp_idf_hal::{
     i2c::{I2cConfig, I2cDriver},
     prelude::*,
 };
 
-use ssd1306::mode::BufferedGraphicsMode;
-use ssd1306::{prelude::*, I2CDisplayInterface, Ssd1306};
-use std::sync::{Arc, Mutex};
+#[cfg(target_os = "espidf")]
+use ssd1306::{mode::BufferedGraphicsMode, I2CDisplayInterface, Ssd1306};
+use ssd1306::prelude::*;
+use std::sync::Arc;
+#[cfg(target_os = "espidf")]
+use std::sync::{Mutex, MutexGuard, PoisonError};
 use std::error::Error;
 use std::fmt;
+use std::cell::RefCell;
+#[cfg(target_os = "espidf")]
+use std::time::Instant;
 
 #[derive(Debug)]
 pub enum DisplayError {
     DriverError,
     DrawError,
+    #[cfg(target_os = "espidf")]
     I2CError(esp_idf_hal::i2c::I2cError),
 }
 
@@ -29,6 +39,7 @@ impl fmt::Display for DisplayError {
         match self {
             DisplayError::DriverError => write!(f, "Display driver initialisation error"),
             DisplayError::DrawError => write!(f, "Error drawing to display"),
+            #[cfg(target_os = "espidf")]
             DisplayError::I2CError(e) => write!(f, "I2C Communication error: {}", e),
         }
     }
@@ -37,12 +48,14 @@ impl fmt::Display for DisplayError {
 impl Error for DisplayError {
     fn source(&self) -> Option<&(dyn Error + 'static)> {
         match self {
+            #[cfg(target_os = "espidf")]
             DisplayError::I2CError(e) => Some(e),
             _ => None,
         }
     }
 }
 
+#[cfg(target_os = "espidf")]
 impl From<esp_idf_hal::i2c::I2cError> for DisplayError {
     fn from(error: esp_idf_hal::i2c::I2cError) -> Self {
         DisplayError::I2CError(error)
@@ -55,21 +68,270 @@ impl From<display_interface::DisplayError> for DisplayError {
     }
 }
 
+pub const DEFAULT_CONTRAST: u8 = 0x7F;
+//most modules are strapped to 0x3C, the rest to 0x3D
+pub const DEFAULT_ADDRESS: u8 = 0x3C;
+#[cfg(target_os = "espidf")]
+const MAX_BUS_FAILURES: u32 = 3;
+
+#[cfg(target_os = "espidf")]
+type Panel128x64 = Ssd1306<I2CInterface<I2cDriver<'static>>, DisplaySize128x64, BufferedGraphicsMode<DisplaySize128x64>>;
+#[cfg(target_os = "espidf")]
+type Panel128x32 = Ssd1306<I2CInterface<I2cDriver<'static>>, DisplaySize128x32, BufferedGraphicsMode<DisplaySize128x32>>;
+
+#[cfg(target_os = "espidf")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PanelSize {
+    Size128x64,
+    Size128x32,
+}
+
+//ssd1306 bakes the panel size into the type, so wrap the supported ones to keep DisplayManager non-generic
+#[cfg(target_os = "espidf")]
+enum PanelDriver {
+    Size128x64(Panel128x64),
+    Size128x32(Panel128x32),
+}
+
+//the driver's framebuffer is private, so keep our own copy of what's been drawn for pixel read-back
+#[cfg(any(target_os = "espidf", feature = "simulator", test))]
+pub(crate) struct Framebuffer {
+    width: u32,
+    height: u32,
+    bits: Vec<u8>,
+}
+
+#[cfg(any(target_os = "espidf", feature = "simulator", test))]
+impl Framebuffer {
+    pub(crate) fn new(size: Size) -> Self {
+        Self {
+            width: size.width,
+            height: size.height,
+            bits: vec![0; (size.width * size.height).div_ceil(8) as usize],
+        }
+    }
+
+    fn index(&self, point: Point) -> Option<usize> {
+        if point.x < 0 || point.y < 0 || point.x as u32 >= self.width || point.y as u32 >= self.height {
+            return None;
+        }
+
+        Some(point.y as usize * self.width as usize + point.x as usize)
+    }
+
+    fn set(&mut self, point: Point, on: bool) {
+        if let Some(index) = self.index(point) {
+            if on {
+                self.bits[index / 8] |= 1 << (index % 8);
+            } else {
+                self.bits[index / 8] &= !(1 << (index % 8));
+            }
+        }
+    }
+
+    pub(crate) fn get(&self, point: Point) -> bool {
+        match self.index(point) {
+            Some(index) => self.bits[index / 8] & (1 << (index % 8)) != 0,
+            None => false,
+        }
+    }
+
+    fn fill(&mut self, area: &Rectangle, on: bool) {
+        for point in area.points() {
+            self.set(point, on);
+        }
+    }
+
+    fn clear(&mut self, on: bool) {
+        self.bits.fill(if on { 0xFF } else { 0x00 });
+    }
+}
+
+#[cfg(any(target_os = "espidf", feature = "simulator", test))]
+impl OriginDimensions for Framebuffer {
+    fn size(&self) -> Size {
+        Size::new(self.width, self.height)
+    }
+}
+
+//drawing straight into the shadow copy is what the host simulator renders into
+#[cfg(any(target_os = "espidf", feature = "simulator", test))]
+impl DrawTarget for Framebuffer {
+    type Color = BinaryColor;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            self.set(point, color.is_on());
+        }
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.fill(area, color.is_on());
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        Framebuffer::clear(self, color.is_on());
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "espidf")]
+pub struct Panel {
+    driver: PanelDriver,
+    shadow: Framebuffer,
+}
+
+#[cfg(target_os = "espidf")]
+impl Panel {
+    fn new(driver: PanelDriver) -> Self {
+        let size = match &driver {
+            PanelDriver::Size128x64(panel) => panel.size(),
+            PanelDriver::Size128x32(panel) => panel.size(),
+        };
+
+        Self {
+            driver,
+            shadow: Framebuffer::new(size),
+        }
+    }
+
+    fn init(&mut self) -> Result<(), display_interface::DisplayError> {
+        match &mut self.driver {
+            PanelDriver::Size128x64(panel) => panel.init(),
+            PanelDriver::Size128x32(panel) => panel.init(),
+        }
+    }
+
+    pub fn flush(&mut self) -> Result<(), display_interface::DisplayError> {
+        match &mut self.driver {
+            PanelDriver::Size128x64(panel) => panel.flush(),
+            PanelDriver::Size128x32(panel) => panel.flush(),
+        }
+    }
+
+    pub fn set_brightness(&mut self, brightness: Brightness) -> Result<(), display_interface::DisplayError> {
+        match &mut self.driver {
+            PanelDriver::Size128x64(panel) => panel.set_brightness(brightness),
+            PanelDriver::Size128x32(panel) => panel.set_brightness(brightness),
+        }
+    }
+
+    pub fn set_rotation(&mut self, rotation: DisplayRotation) -> Result<(), display_interface::DisplayError> {
+        match &mut self.driver {
+            PanelDriver::Size128x64(panel) => panel.set_rotation(rotation)?,
+            PanelDriver::Size128x32(panel) => panel.set_rotation(rotation)?,
+        }
+
+        //going between landscape and portrait swaps width and height, the old contents
+        //don't map onto the new shape so start both buffers over blank
+        if self.shadow.size() != self.size() {
+            self.shadow = Framebuffer::new(self.size());
+            self.clear(BinaryColor::Off)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn set_display_on(&mut self, on: bool) -> Result<(), display_interface::DisplayError> {
+        match &mut self.driver {
+            PanelDriver::Size128x64(panel) => panel.set_display_on(on),
+            PanelDriver::Size128x32(panel) => panel.set_display_on(on),
+        }
+    }
+
+    pub fn get_pixel(&self, point: Point) -> bool {
+        self.shadow.get(point)
+    }
+}
+
+#[cfg(target_os = "espidf")]
+impl OriginDimensions for Panel {
+    fn size(&self) -> Size {
+        match &self.driver {
+            PanelDriver::Size128x64(panel) => panel.size(),
+            PanelDriver::Size128x32(panel) => panel.size(),
+        }
+    }
+}
+
+#[cfg(target_os = "espidf")]
+impl DrawTarget for Panel {
+    type Color = BinaryColor;
+    type Error = display_interface::DisplayError;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let shadow = &mut self.shadow;
+        let pixels = pixels.into_iter().inspect(|Pixel(point, color)| shadow.set(*point, color.is_on()));
+
+        match &mut self.driver {
+            PanelDriver::Size128x64(panel) => panel.draw_iter(pixels),
+            PanelDriver::Size128x32(panel) => panel.draw_iter(pixels),
+        }
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.shadow.fill(area, color.is_on());
+
+        match &mut self.driver {
+            PanelDriver::Size128x64(panel) => panel.fill_solid(area, color),
+            PanelDriver::Size128x32(panel) => panel.fill_solid(area, color),
+        }
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.shadow.clear(color.is_on());
+
+        match &mut self.driver {
+            PanelDriver::Size128x64(panel) => panel.clear(color),
+            PanelDriver::Size128x32(panel) => panel.clear(color),
+        }
+    }
+}
+
+#[cfg(target_os = "espidf")]
 pub struct DisplayManager {
-    display: Arc<Mutex<Ssd1306<I2CInterface<I2cDriver<'static>>, DisplaySize128x64, BufferedGraphicsMode<DisplaySize128x64>>>>,
+    display: Arc<Mutex<Panel>>,
+    dirty: Mutex<Option<Rectangle>>,
+    contrast: Mutex<u8>,
+    bus_failures: Mutex<u32>,
+    flush_held: Mutex<bool>,
 }
 
+#[cfg(target_os = "espidf")]
 impl DisplayManager {
     pub fn new(i2c: I2cDriver<'static>) -> Result<Self, DisplayError> {
-        let interface = I2CDisplayInterface::new_custom_address(i2c, 0x3C);
+        Self::new_with_rotation(i2c, DisplayRotation::Rotate180)
+    }
+
+    pub fn new_with_rotation(i2c: I2cDriver<'static>, rotation: DisplayRotation) -> Result<Self, DisplayError> {
+        Self::new_with_config(i2c, DEFAULT_ADDRESS, PanelSize::Size128x64, rotation)
+    }
+
+    pub fn new_with_address(i2c: I2cDriver<'static>, address: u8) -> Result<Self, DisplayError> {
+        Self::new_with_config(i2c, address, PanelSize::Size128x64, DisplayRotation::Rotate180)
+    }
+
+    pub fn new_with_config(i2c: I2cDriver<'static>, address: u8, size: PanelSize, rotation: DisplayRotation) -> Result<Self, DisplayError> {
+        let interface = I2CDisplayInterface::new_custom_address(i2c, address);
 
-        log::info!("Creating display...");
-        let mut display = Ssd1306::new(
-            interface,
-            DisplaySize128x64,
-            DisplayRotation::Rotate180,
-        )
-        .into_buffered_graphics_mode();
+        log::info!("Creating display at 0x{:02X} ({:?}, {:?})...", address, size, rotation);
+        let mut display = Panel::new(match size {
+            PanelSize::Size128x64 => PanelDriver::Size128x64(
+                Ssd1306::new(interface, DisplaySize128x64, rotation).into_buffered_graphics_mode(),
+            ),
+            PanelSize::Size128x32 => PanelDriver::Size128x32(
+                Ssd1306::new(interface, DisplaySize128x32, rotation).into_buffered_graphics_mode(),
+            ),
+        });
 
         log::info!("Initializing display...");
         match display.init() {
@@ -80,6 +342,10 @@ impl DisplayManager {
             }
         }
         
+        if let Err(e) = display.set_brightness(Brightness::custom(0x2, DEFAULT_CONTRAST)) {
+            log::warn!("Failed to set initial contrast: {:?}", e);
+        }
+
         match display.clear(BinaryColor::Off) {
             Ok(_) => log::info!("Display cleared successfully"),
             Err(e) => {
@@ -90,72 +356,222 @@ impl DisplayManager {
 
         Ok(Self {
             display: Arc::new(Mutex::new(display)),
+            dirty: Mutex::new(None),
+            contrast: Mutex::new(DEFAULT_CONTRAST),
+            bus_failures: Mutex::new(0),
+            flush_held: Mutex::new(false),
         })
     }
 
-    pub fn clear(&self) -> Result<(), DisplayError> {
-        let mut display = self.display.lock().unwrap();
-        display.clear(BinaryColor::Off).map_err(|_| DisplayError::DrawError)?;
+    //a panic mid-draw poisons the mutex, but the framebuffer is still usable so carry on with it
+    fn lock_display(&self) -> MutexGuard<'_, Panel> {
+        self.display.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn reinit(&self) -> Result<(), DisplayError> {
+        log::warn!("Reinitialising display...");
+        let mut display = self.lock_display();
+
+        if let Err(e) = display.init() {
+            log::error!("Display reinitialisation failed: {:?}", e);
+            return Err(DisplayError::DriverError);
+        }
+
+        let contrast = *self.contrast.lock().unwrap_or_else(PoisonError::into_inner);
+        display.set_brightness(Brightness::custom(0x2, contrast))
+            .map_err(|_| DisplayError::DriverError)?;
+
+        //panel RAM is blank after init, the next full draw puts the screen back
+        log::info!("Display reinitialised");
         Ok(())
     }
 
+    fn check_bus(&self, result: Result<(), display_interface::DisplayError>) -> Result<(), DisplayError> {
+        let mut failures = self.bus_failures.lock().unwrap_or_else(PoisonError::into_inner);
+
+        match result {
+            Ok(_) => {
+                *failures = 0;
+                Ok(())
+            },
+            Err(e) => {
+                *failures += 1;
+                log::warn!("Display bus write failed ({} in a row): {:?}", *failures, e);
+
+                if *failures >= MAX_BUS_FAILURES {
+                    *failures = 0;
+                    drop(failures);
+                    self.reinit()?;
+                }
+
+                Err(DisplayError::DrawError)
+            },
+        }
+    }
+
     pub fn flush(&self) -> Result<(), DisplayError> {
-        let mut display = self.display.lock().unwrap();
-        display.flush().map_err(|_| DisplayError::DrawError)?;
+        if self.is_flush_held() {
+            return Ok(());
+        }
+
+        let result = self.lock_display().flush();
+        self.check_bus(result)?;
+        *self.dirty.lock().unwrap_or_else(PoisonError::into_inner) = None;
         Ok(())
     }
 
-    pub fn draw_text(&self, text:&str, x: i32, y: i32, size: TextSize) -> Result<(), DisplayError> {
-        let mut display_guard = self.display.lock().unwrap();
-        let display = &mut *display_guard;
+    //the panel lock is released before check_bus, a reinit takes it again
+    pub fn set_contrast(&self, value: u8) -> Result<(), DisplayError> {
+        let result = self.lock_display().set_brightness(Brightness::custom(0x2, value));
+        self.check_bus(result).map_err(|_| DisplayError::DriverError)?;
+        *self.contrast.lock().unwrap_or_else(PoisonError::into_inner) = value;
+        Ok(())
+    }
+
+    pub fn contrast(&self) -> u8 {
+        *self.contrast.lock().unwrap_or_else(PoisonError::into_inner)
+    }
 
-        let font = match size {
-            TextSize::Small => &FONT_6X10,
-            TextSize::Normal => &FONT_6X10,
-            TextSize::Large => &FONT_6X10,
+    pub fn set_display_on(&self, on: bool) -> Result<(), DisplayError> {
+        //panel goes dark but the framebuffer is kept, so turning back on needs no redraw
+        let result = self.lock_display().set_display_on(on);
+        self.check_bus(result).map_err(|_| DisplayError::DriverError)
+    }
+
+    pub fn set_rotation(&self, rotation: DisplayRotation) -> Result<(), DisplayError> {
+        let result = self.lock_display().set_rotation(rotation);
+        self.check_bus(result).map_err(|_| DisplayError::DriverError)
+    }
+
+    pub fn mark_dirty(&self, area: Rectangle) {
+        if area.is_zero_sized() {
+            return;
+        }
+
+        let mut dirty = self.dirty.lock().unwrap_or_else(PoisonError::into_inner);
+        *dirty = Some(match dirty.take() {
+            Some(current) => union_rects(&current, &area),
+            None => area,
+        });
+    }
+
+    pub fn flush_dirty(&self) -> Result<(), DisplayError> {
+        //keep the dirty area so the flush after release still covers it
+        if self.is_flush_held() {
+            return Ok(());
+        }
+
+        let area = match self.dirty.lock().unwrap_or_else(PoisonError::into_inner).take() {
+            Some(area) => area,
+            None => return Ok(()),
         };
 
-        let text_style = MonoTextStyleBuilder::new()
-            .font(font)
-            .text_color(BinaryColor::On)
-            .build();
+        let started = Instant::now();
 
-        let text_obj = Text::with_baseline(
-            text,
-            Point::new(x, y),
-            text_style,
-            Baseline::Top,
-        );
-            
-        text_obj.draw(display)
-        .map_err(|_| DisplayError::DrawError)?;
+        //the ssd1306 driver only sends the column/page window it saw change since the last flush
+        let (result, size) = {
+            let mut display = self.lock_display();
+            (display.flush(), display.size())
+        };
+        self.check_bus(result)?;
+
+        let first_page = area.top_left.y / 8;
+        let last_page = (area.top_left.y + area.size.height as i32 - 1) / 8;
+        let bytes = area.size.width * (last_page - first_page + 1) as u32;
+        let full_frame = size.width * size.height / 8;
+        log::debug!("flush_dirty: ~{} of {} bytes in {:?}", bytes, full_frame, started.elapsed());
 
         Ok(())
     }
 
-    pub fn draw_rectangle(&self, x: i32, y:i32, width: u32, height: u32, filled: bool) -> Result<(), DisplayError> {
-        let mut display_guard = self.display.lock().unwrap();
-        let display = &mut *display_guard;
-
-        let rect = Rectangle::new(
-            Point::new(x, y),
-            Size::new(width, height),
-        );
+    //writes straight into the framebuffer, batch as many as needed and flush() once
+    pub fn set_pixel(&self, x: i32, y: i32, on: bool) -> Result<(), DisplayError> {
+        let mut display = self.lock_display();
+        let point = Point::new(x, y);
 
-        if filled {
-            rect.into_styled(PrimitiveStyle::with_fill(BinaryColor::On))
-                .draw(display)
-                .map_err(|_| DisplayError::DrawError)?;
-        } else {
-            rect.into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
-                .draw(display)
-                .map_err(|_| DisplayError::DrawError)?;
+        if !display.bounding_box().contains(point) {
+            return Err(DisplayError::DrawError);
         }
 
+        let color = if on { BinaryColor::On } else { BinaryColor::Off };
+        Pixel(point, color).draw(&mut *display)
+            .map_err(|_| DisplayError::DrawError)?;
+
+        drop(display);
+        self.mark_dirty(Rectangle::new(point, Size::new(1, 1)));
         Ok(())
     }
 
-    pub fn draw_progress_bar(&self, x: i32, y: i32, width: u32, progress: u8) -> Result<(), DisplayError> {
+    pub fn get_pixel(&self, x: i32, y: i32) -> bool {
+        self.lock_display().get_pixel(Point::new(x, y))
+    }
+
+    pub fn capture(&self) -> Vec<u8> {
+        let display = self.lock_display();
+        pack_pixels(display.size(), |point| display.get_pixel(point))
+    }
+
+    //while held, drawing still lands in the framebuffer but nothing is sent to the panel
+    pub fn hold_flush(&self, hold: bool) {
+        *self.flush_held.lock().unwrap_or_else(PoisonError::into_inner) = hold;
+    }
+
+    fn is_flush_held(&self) -> bool {
+        *self.flush_held.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    //raw DrawTarget access for embedded-graphics drawables, the whole panel counts as dirty afterwards
+    pub fn draw_target(&self) -> MutexGuard<'_, Panel> {
+        let display = self.lock_display();
+        self.mark_dirty(display.bounding_box());
+        display
+    }
+
+    pub fn draw_batch<F>(&self, f: F) -> Result<(), DisplayError>
+    where
+        F: FnOnce(&DrawContext) -> Result<(), DisplayError>,
+    {
+        let context = DrawContext {
+            panel: RefCell::new(self.lock_display()),
+            dirty: RefCell::new(None),
+        };
+
+        let result = f(&context);
+
+        //release the panel before flushing, flush_dirty takes the lock itself
+        let DrawContext { panel, dirty } = context;
+        drop(panel);
+
+        //whatever was drawn before a failure is in the framebuffer, so it's still owed a flush
+        if let Some(area) = dirty.into_inner() {
+            self.mark_dirty(area);
+        }
+
+        result?;
+        self.flush_dirty()
+    }
+
+    pub fn get_display_clone(&self) -> Arc<Mutex<Panel>> {
+        self.display.clone()
+    }
+
+}
+
+pub trait Canvas {
+    fn clear(&self) -> Result<(), DisplayError>;
+    fn draw_text(&self, text: &str, x: i32, y: i32, size: TextSize) -> Result<(), DisplayError>;
+    fn draw_text_clipped(&self, text: &str, x: i32, y: i32, size: TextSize, clip_x: i32, clip_width: u32) -> Result<(), DisplayError>;
+    fn draw_text_inverted(&self, text: &str, x: i32, y: i32, size: TextSize) -> Result<(), DisplayError>;
+    fn draw_rectangle(&self, x: i32, y: i32, width: u32, height: u32, filled: bool) -> Result<(), DisplayError>;
+    fn draw_rounded_rectangle(&self, x: i32, y: i32, width: u32, height: u32, radius: u32, filled: bool) -> Result<(), DisplayError>;
+    fn draw_line(&self, x0: i32, y0: i32, x1: i32, y1: i32) -> Result<(), DisplayError>;
+    fn draw_circle(&self, cx: i32, cy: i32, radius: u32, filled: bool) -> Result<(), DisplayError>;
+    fn draw_bitmap(&self, x: i32, y: i32, width: u32, data: &[u8]) -> Result<(), DisplayError>;
+    fn clear_region(&self, x: i32, y: i32, width: u32, height: u32) -> Result<(), DisplayError>;
+    fn width(&self) -> u32;
+    fn height(&self) -> u32;
+
+    fn draw_progress_bar(&self, x: i32, y: i32, width: u32, progress: u8) -> Result<(), DisplayError> {
         let height = 8u32;
         let progress = progress.min(100) as u32;
         let fill_width = (width*progress)/100;
@@ -169,15 +585,742 @@ impl DisplayManager {
         Ok(())
     }
 
-    pub fn get_display_clone(&self) -> Arc<Mutex<Ssd1306<I2CInterface<I2cDriver<'static>>, DisplaySize128x64, BufferedGraphicsMode<DisplaySize128x64>>>> {
-        self.display.clone()
+    //clipped to the panel up front, a line that misses it entirely draws nothing
+    fn draw_hline(&self, x: i32, y: i32, length: u32) -> Result<(), DisplayError> {
+        if length == 0 || y < 0 || y >= self.height() as i32 {
+            return Ok(());
+        }
+
+        let start = x.max(0);
+        let end = (x + length as i32 - 1).min(self.width() as i32 - 1);
+
+        if start > end {
+            return Ok(());
+        }
+
+        self.draw_line(start, y, end, y)
+    }
+
+    fn draw_vline(&self, x: i32, y: i32, length: u32) -> Result<(), DisplayError> {
+        if length == 0 || x < 0 || x >= self.width() as i32 {
+            return Ok(());
+        }
+
+        let start = y.max(0);
+        let end = (y + length as i32 - 1).min(self.height() as i32 - 1);
+
+        if start > end {
+            return Ok(());
+        }
+
+        self.draw_line(x, start, x, end)
+    }
+}
+
+#[cfg(target_os = "espidf")]
+impl Canvas for DisplayManager {
+    fn clear(&self) -> Result<(), DisplayError> {
+        let area = clear_panel(&mut *self.lock_display())?;
+        self.mark_dirty(area);
+        Ok(())
+    }
+
+    fn draw_text(&self, text: &str, x: i32, y: i32, size: TextSize) -> Result<(), DisplayError> {
+        let area = render_text(&mut *self.lock_display(), text, x, y, size, None)?;
+        self.mark_dirty(area);
+        Ok(())
+    }
+
+    fn draw_text_clipped(&self, text: &str, x: i32, y: i32, size: TextSize, clip_x: i32, clip_width: u32) -> Result<(), DisplayError> {
+        let area = render_text(&mut *self.lock_display(), text, x, y, size, Some((clip_x, clip_width)))?;
+        self.mark_dirty(area);
+        Ok(())
+    }
+
+    fn draw_text_inverted(&self, text: &str, x: i32, y: i32, size: TextSize) -> Result<(), DisplayError> {
+        let area = render_text_inverted(&mut *self.lock_display(), text, x, y, size)?;
+        self.mark_dirty(area);
+        Ok(())
+    }
+
+    fn draw_rectangle(&self, x: i32, y: i32, width: u32, height: u32, filled: bool) -> Result<(), DisplayError> {
+        let area = render_rectangle(&mut *self.lock_display(), x, y, width, height, filled)?;
+        self.mark_dirty(area);
+        Ok(())
+    }
+
+    fn draw_rounded_rectangle(&self, x: i32, y: i32, width: u32, height: u32, radius: u32, filled: bool) -> Result<(), DisplayError> {
+        let area = render_rounded_rectangle(&mut *self.lock_display(), x, y, width, height, radius, filled)?;
+        self.mark_dirty(area);
+        Ok(())
+    }
+
+    fn draw_line(&self, x0: i32, y0: i32, x1: i32, y1: i32) -> Result<(), DisplayError> {
+        let area = render_line(&mut *self.lock_display(), x0, y0, x1, y1)?;
+        self.mark_dirty(area);
+        Ok(())
+    }
+
+    fn draw_circle(&self, cx: i32, cy: i32, radius: u32, filled: bool) -> Result<(), DisplayError> {
+        let area = render_circle(&mut *self.lock_display(), cx, cy, radius, filled)?;
+        self.mark_dirty(area);
+        Ok(())
+    }
+
+    fn draw_bitmap(&self, x: i32, y: i32, width: u32, data: &[u8]) -> Result<(), DisplayError> {
+        let area = render_bitmap(&mut *self.lock_display(), x, y, width, data)?;
+        self.mark_dirty(area);
+        Ok(())
+    }
+
+    fn clear_region(&self, x: i32, y: i32, width: u32, height: u32) -> Result<(), DisplayError> {
+        let area = render_clear_region(&mut *self.lock_display(), x, y, width, height)?;
+        self.mark_dirty(area);
+        Ok(())
+    }
+
+    fn width(&self) -> u32 {
+        self.lock_display().size().width
+    }
+
+    fn height(&self) -> u32 {
+        self.lock_display().size().height
+    }
+}
+
+//lets screens pass their display handle straight to Widget::draw
+impl<T: Canvas + ?Sized> Canvas for Arc<T> {
+    fn clear(&self) -> Result<(), DisplayError> {
+        (**self).clear()
     }
 
+    fn draw_text(&self, text: &str, x: i32, y: i32, size: TextSize) -> Result<(), DisplayError> {
+        (**self).draw_text(text, x, y, size)
+    }
+
+    fn draw_text_clipped(&self, text: &str, x: i32, y: i32, size: TextSize, clip_x: i32, clip_width: u32) -> Result<(), DisplayError> {
+        (**self).draw_text_clipped(text, x, y, size, clip_x, clip_width)
+    }
+
+    fn draw_text_inverted(&self, text: &str, x: i32, y: i32, size: TextSize) -> Result<(), DisplayError> {
+        (**self).draw_text_inverted(text, x, y, size)
+    }
+
+    fn draw_rectangle(&self, x: i32, y: i32, width: u32, height: u32, filled: bool) -> Result<(), DisplayError> {
+        (**self).draw_rectangle(x, y, width, height, filled)
+    }
+
+    fn draw_rounded_rectangle(&self, x: i32, y: i32, width: u32, height: u32, radius: u32, filled: bool) -> Result<(), DisplayError> {
+        (**self).draw_rounded_rectangle(x, y, width, height, radius, filled)
+    }
+
+    fn draw_line(&self, x0: i32, y0: i32, x1: i32, y1: i32) -> Result<(), DisplayError> {
+        (**self).draw_line(x0, y0, x1, y1)
+    }
+
+    fn draw_circle(&self, cx: i32, cy: i32, radius: u32, filled: bool) -> Result<(), DisplayError> {
+        (**self).draw_circle(cx, cy, radius, filled)
+    }
+
+    fn draw_bitmap(&self, x: i32, y: i32, width: u32, data: &[u8]) -> Result<(), DisplayError> {
+        (**self).draw_bitmap(x, y, width, data)
+    }
+
+    fn clear_region(&self, x: i32, y: i32, width: u32, height: u32) -> Result<(), DisplayError> {
+        (**self).clear_region(x, y, width, height)
+    }
+
+    fn width(&self) -> u32 {
+        (**self).width()
+    }
+
+    fn height(&self) -> u32 {
+        (**self).height()
+    }
 }
 
-#[derive(Debug, Clone, Copy)]
+//everything screens need from a display beyond drawing, so the same code runs on the ssd1306 or the host simulator
+pub trait DisplayBackend: Canvas + Send + Sync {
+    fn flush(&self) -> Result<(), DisplayError>;
+    fn flush_dirty(&self) -> Result<(), DisplayError>;
+    fn draw_frame(&self, f: &mut dyn FnMut(&dyn Canvas) -> Result<(), DisplayError>) -> Result<(), DisplayError>;
+    fn set_contrast(&self, value: u8) -> Result<(), DisplayError>;
+    fn contrast(&self) -> u8;
+    fn set_display_on(&self, on: bool) -> Result<(), DisplayError>;
+    fn set_rotation(&self, rotation: DisplayRotation) -> Result<(), DisplayError>;
+
+    //the frame as a draw_bitmap-style 1bpp image, None if the backend can't read its pixels back
+    fn capture(&self) -> Option<Vec<u8>> {
+        None
+    }
+
+    //while held, flushes leave the panel alone; backends that can't hold just keep flushing
+    fn hold_flush(&self, _hold: bool) {}
+}
+
+#[cfg(target_os = "espidf")]
+impl DisplayBackend for DisplayManager {
+    fn flush(&self) -> Result<(), DisplayError> {
+        DisplayManager::flush(self)
+    }
+
+    fn flush_dirty(&self) -> Result<(), DisplayError> {
+        DisplayManager::flush_dirty(self)
+    }
+
+    fn draw_frame(&self, f: &mut dyn FnMut(&dyn Canvas) -> Result<(), DisplayError>) -> Result<(), DisplayError> {
+        self.draw_batch(|ctx| f(ctx))
+    }
+
+    fn set_contrast(&self, value: u8) -> Result<(), DisplayError> {
+        DisplayManager::set_contrast(self, value)
+    }
+
+    fn contrast(&self) -> u8 {
+        DisplayManager::contrast(self)
+    }
+
+    fn set_display_on(&self, on: bool) -> Result<(), DisplayError> {
+        DisplayManager::set_display_on(self, on)
+    }
+
+    fn set_rotation(&self, rotation: DisplayRotation) -> Result<(), DisplayError> {
+        DisplayManager::set_rotation(self, rotation)
+    }
+
+    fn capture(&self) -> Option<Vec<u8>> {
+        Some(DisplayManager::capture(self))
+    }
+
+    fn hold_flush(&self, hold: bool) {
+        DisplayManager::hold_flush(self, hold)
+    }
+}
+
+#[cfg(target_os = "espidf")]
+pub struct DrawContext<'a> {
+    panel: RefCell<MutexGuard<'a, Panel>>,
+    dirty: RefCell<Option<Rectangle>>,
+}
+
+#[cfg(target_os = "espidf")]
+impl DrawContext<'_> {
+    fn extend_dirty(&self, area: Rectangle) {
+        if area.is_zero_sized() {
+            return;
+        }
+
+        let mut dirty = self.dirty.borrow_mut();
+        *dirty = Some(match dirty.take() {
+            Some(current) => union_rects(&current, &area),
+            None => area,
+        });
+    }
+}
+
+#[cfg(target_os = "espidf")]
+impl Canvas for DrawContext<'_> {
+    fn clear(&self) -> Result<(), DisplayError> {
+        let area = clear_panel(&mut **self.panel.borrow_mut())?;
+        self.extend_dirty(area);
+        Ok(())
+    }
+
+    fn draw_text(&self, text: &str, x: i32, y: i32, size: TextSize) -> Result<(), DisplayError> {
+        let area = render_text(&mut **self.panel.borrow_mut(), text, x, y, size, None)?;
+        self.extend_dirty(area);
+        Ok(())
+    }
+
+    fn draw_text_clipped(&self, text: &str, x: i32, y: i32, size: TextSize, clip_x: i32, clip_width: u32) -> Result<(), DisplayError> {
+        let area = render_text(&mut **self.panel.borrow_mut(), text, x, y, size, Some((clip_x, clip_width)))?;
+        self.extend_dirty(area);
+        Ok(())
+    }
+
+    fn draw_text_inverted(&self, text: &str, x: i32, y: i32, size: TextSize) -> Result<(), DisplayError> {
+        let area = render_text_inverted(&mut **self.panel.borrow_mut(), text, x, y, size)?;
+        self.extend_dirty(area);
+        Ok(())
+    }
+
+    fn draw_rectangle(&self, x: i32, y: i32, width: u32, height: u32, filled: bool) -> Result<(), DisplayError> {
+        let area = render_rectangle(&mut **self.panel.borrow_mut(), x, y, width, height, filled)?;
+        self.extend_dirty(area);
+        Ok(())
+    }
+
+    fn draw_rounded_rectangle(&self, x: i32, y: i32, width: u32, height: u32, radius: u32, filled: bool) -> Result<(), DisplayError> {
+        let area = render_rounded_rectangle(&mut **self.panel.borrow_mut(), x, y, width, height, radius, filled)?;
+        self.extend_dirty(area);
+        Ok(())
+    }
+
+    fn draw_line(&self, x0: i32, y0: i32, x1: i32, y1: i32) -> Result<(), DisplayError> {
+        let area = render_line(&mut **self.panel.borrow_mut(), x0, y0, x1, y1)?;
+        self.extend_dirty(area);
+        Ok(())
+    }
+
+    fn draw_circle(&self, cx: i32, cy: i32, radius: u32, filled: bool) -> Result<(), DisplayError> {
+        let area = render_circle(&mut **self.panel.borrow_mut(), cx, cy, radius, filled)?;
+        self.extend_dirty(area);
+        Ok(())
+    }
+
+    fn draw_bitmap(&self, x: i32, y: i32, width: u32, data: &[u8]) -> Result<(), DisplayError> {
+        let area = render_bitmap(&mut **self.panel.borrow_mut(), x, y, width, data)?;
+        self.extend_dirty(area);
+        Ok(())
+    }
+
+    fn clear_region(&self, x: i32, y: i32, width: u32, height: u32) -> Result<(), DisplayError> {
+        let area = render_clear_region(&mut **self.panel.borrow_mut(), x, y, width, height)?;
+        self.extend_dirty(area);
+        Ok(())
+    }
+
+    fn width(&self) -> u32 {
+        self.panel.borrow().size().width
+    }
+
+    fn height(&self) -> u32 {
+        self.panel.borrow().size().height
+    }
+}
+
+//swallows the target's errors so the render helpers can run on it, keeping the first one to hand back
+struct CaptureErrors<'a, D: DrawTarget<Color = BinaryColor>> {
+    target: &'a mut D,
+    error: Option<D::Error>,
+}
+
+impl<D: DrawTarget<Color = BinaryColor>> Dimensions for CaptureErrors<'_, D> {
+    fn bounding_box(&self) -> Rectangle {
+        self.target.bounding_box()
+    }
+}
+
+impl<D: DrawTarget<Color = BinaryColor>> DrawTarget for CaptureErrors<'_, D> {
+    type Color = BinaryColor;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        if self.error.is_none() {
+            if let Err(e) = self.target.draw_iter(pixels) {
+                self.error = Some(e);
+            }
+        }
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        if self.error.is_none() {
+            if let Err(e) = self.target.fill_solid(area, color) {
+                self.error = Some(e);
+            }
+        }
+        Ok(())
+    }
+}
+
+//a Canvas over any embedded-graphics target, so widgets can draw somewhere other than the ssd1306
+pub struct TargetCanvas<'a, D: DrawTarget<Color = BinaryColor>> {
+    target: RefCell<CaptureErrors<'a, D>>,
+}
+
+impl<'a, D: DrawTarget<Color = BinaryColor>> TargetCanvas<'a, D> {
+    pub fn new(target: &'a mut D) -> Self {
+        Self {
+            target: RefCell::new(CaptureErrors { target, error: None }),
+        }
+    }
+
+    pub fn into_error(self) -> Option<D::Error> {
+        self.target.into_inner().error
+    }
+}
+
+impl<D: DrawTarget<Color = BinaryColor>> Canvas for TargetCanvas<'_, D> {
+    fn clear(&self) -> Result<(), DisplayError> {
+        clear_panel(&mut *self.target.borrow_mut()).map(|_| ())
+    }
+
+    fn draw_text(&self, text: &str, x: i32, y: i32, size: TextSize) -> Result<(), DisplayError> {
+        render_text(&mut *self.target.borrow_mut(), text, x, y, size, None).map(|_| ())
+    }
+
+    fn draw_text_clipped(&self, text: &str, x: i32, y: i32, size: TextSize, clip_x: i32, clip_width: u32) -> Result<(), DisplayError> {
+        render_text(&mut *self.target.borrow_mut(), text, x, y, size, Some((clip_x, clip_width))).map(|_| ())
+    }
+
+    fn draw_text_inverted(&self, text: &str, x: i32, y: i32, size: TextSize) -> Result<(), DisplayError> {
+        render_text_inverted(&mut *self.target.borrow_mut(), text, x, y, size).map(|_| ())
+    }
+
+    fn draw_rectangle(&self, x: i32, y: i32, width: u32, height: u32, filled: bool) -> Result<(), DisplayError> {
+        render_rectangle(&mut *self.target.borrow_mut(), x, y, width, height, filled).map(|_| ())
+    }
+
+    fn draw_rounded_rectangle(&self, x: i32, y: i32, width: u32, height: u32, radius: u32, filled: bool) -> Result<(), DisplayError> {
+        render_rounded_rectangle(&mut *self.target.borrow_mut(), x, y, width, height, radius, filled).map(|_| ())
+    }
+
+    fn draw_line(&self, x0: i32, y0: i32, x1: i32, y1: i32) -> Result<(), DisplayError> {
+        render_line(&mut *self.target.borrow_mut(), x0, y0, x1, y1).map(|_| ())
+    }
+
+    fn draw_circle(&self, cx: i32, cy: i32, radius: u32, filled: bool) -> Result<(), DisplayError> {
+        render_circle(&mut *self.target.borrow_mut(), cx, cy, radius, filled).map(|_| ())
+    }
+
+    fn draw_bitmap(&self, x: i32, y: i32, width: u32, data: &[u8]) -> Result<(), DisplayError> {
+        render_bitmap(&mut *self.target.borrow_mut(), x, y, width, data).map(|_| ())
+    }
+
+    fn clear_region(&self, x: i32, y: i32, width: u32, height: u32) -> Result<(), DisplayError> {
+        render_clear_region(&mut *self.target.borrow_mut(), x, y, width, height).map(|_| ())
+    }
+
+    fn width(&self) -> u32 {
+        self.target.borrow().bounding_box().size.width
+    }
+
+    fn height(&self) -> u32 {
+        self.target.borrow().bounding_box().size.height
+    }
+}
+
+//the render_* helpers do the actual drawing against a locked panel (or any other target) and return the area they touched
+
+pub(crate) fn clear_panel<D: DrawTarget<Color = BinaryColor>>(display: &mut D) -> Result<Rectangle, DisplayError> {
+    display.clear(BinaryColor::Off).map_err(|_| DisplayError::DrawError)?;
+    Ok(display.bounding_box())
+}
+
+pub(crate) fn render_clear_region<D: DrawTarget<Color = BinaryColor>>(display: &mut D, x: i32, y: i32, width: u32, height: u32) -> Result<Rectangle, DisplayError> {
+    //only the cleared area ends up in the driver's dirty window, unlike clear()
+    let area = Rectangle::new(Point::new(x, y), Size::new(width, height))
+        .intersection(&display.bounding_box());
+
+    display.fill_solid(&area, BinaryColor::Off)
+        .map_err(|_| DisplayError::DrawError)?;
+
+    Ok(area)
+}
+
+pub(crate) fn render_text<D: DrawTarget<Color = BinaryColor>>(display: &mut D, text: &str, x: i32, y: i32, size: TextSize, clip: Option<(i32, u32)>) -> Result<Rectangle, DisplayError> {
+    let font = font_for(size);
+    let (_, line_height) = font_metrics(size);
+
+    let text_style = MonoTextStyleBuilder::new()
+        .font(font)
+        .text_color(BinaryColor::On)
+        .build();
+
+    let mut area: Option<Rectangle> = None;
+
+    //lines() drops a trailing newline, so it never costs an empty row below the text
+    for (index, line) in text.lines().enumerate() {
+        //blank lines still take up a row, there's just nothing to draw
+        if line.is_empty() {
+            continue;
+        }
+
+        let line_y = y + index as i32 * line_height as i32;
+        let text_obj = Text::with_baseline(
+            line,
+            Point::new(x, line_y),
+            text_style,
+            Baseline::Top,
+        );
+
+        let line_area = match clip {
+            Some((clip_x, clip_width)) => {
+                let clip = Rectangle::new(Point::new(clip_x, line_y), Size::new(clip_width, line_height));
+
+                text_obj.draw(&mut display.clipped(&clip))
+                    .map_err(|_| DisplayError::DrawError)?;
+
+                clip
+            },
+            None => {
+                text_obj.draw(display)
+                    .map_err(|_| DisplayError::DrawError)?;
+
+                text_obj.bounding_box()
+            },
+        };
+
+        area = Some(match area {
+            Some(current) => union_rects(&current, &line_area),
+            None => line_area,
+        });
+    }
+
+    Ok(area.unwrap_or(Rectangle::zero()))
+}
+
+pub(crate) fn render_text_inverted<D: DrawTarget<Color = BinaryColor>>(display: &mut D, text: &str, x: i32, y: i32, size: TextSize) -> Result<Rectangle, DisplayError> {
+    let font = font_for(size);
+    let (width, height) = measure_text(text, size);
+    let highlight = Rectangle::new(Point::new(x, y), Size::new(width, height));
+
+    highlight.into_styled(PrimitiveStyle::with_fill(BinaryColor::On))
+        .draw(display)
+        .map_err(|_| DisplayError::DrawError)?;
+
+    let text_style = MonoTextStyleBuilder::new()
+        .font(font)
+        .text_color(BinaryColor::Off)
+        .build();
+
+    Text::with_baseline(text, Point::new(x, y), text_style, Baseline::Top)
+        .draw(display)
+        .map_err(|_| DisplayError::DrawError)?;
+
+    Ok(highlight)
+}
+
+pub(crate) fn render_rectangle<D: DrawTarget<Color = BinaryColor>>(display: &mut D, x: i32, y: i32, width: u32, height: u32, filled: bool) -> Result<Rectangle, DisplayError> {
+    let rect = Rectangle::new(
+        Point::new(x, y),
+        Size::new(width, height),
+    );
+
+    if filled {
+        rect.into_styled(PrimitiveStyle::with_fill(BinaryColor::On))
+            .draw(display)
+            .map_err(|_| DisplayError::DrawError)?;
+    } else {
+        rect.into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+            .draw(display)
+            .map_err(|_| DisplayError::DrawError)?;
+    }
+
+    Ok(rect)
+}
+
+//radius is capped at half the shorter side, 0 is just a plain rectangle
+pub(crate) fn render_rounded_rectangle<D: DrawTarget<Color = BinaryColor>>(display: &mut D, x: i32, y: i32, width: u32, height: u32, radius: u32, filled: bool) -> Result<Rectangle, DisplayError> {
+    let radius = radius.min(width.min(height) / 2);
+    if radius == 0 {
+        return render_rectangle(display, x, y, width, height, filled);
+    }
+
+    let rect = Rectangle::new(Point::new(x, y), Size::new(width, height));
+    let rounded = RoundedRectangle::new(rect, CornerRadii::new(Size::new(radius, radius)));
+
+    let style = if filled {
+        PrimitiveStyle::with_fill(BinaryColor::On)
+    } else {
+        PrimitiveStyle::with_stroke(BinaryColor::On, 1)
+    };
+
+    rounded.into_styled(style)
+        .draw(display)
+        .map_err(|_| DisplayError::DrawError)?;
+
+    Ok(rect)
+}
+
+pub(crate) fn render_line<D: DrawTarget<Color = BinaryColor>>(display: &mut D, x0: i32, y0: i32, x1: i32, y1: i32) -> Result<Rectangle, DisplayError> {
+    //clip to the panel so off-screen endpoints just get cut off
+    let area = display.bounding_box();
+    let mut clipped = display.clipped(&area);
+
+    let line = Line::new(Point::new(x0, y0), Point::new(x1, y1));
+    line.into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+        .draw(&mut clipped)
+        .map_err(|_| DisplayError::DrawError)?;
+
+    Ok(line.bounding_box().intersection(&area))
+}
+
+pub(crate) fn render_circle<D: DrawTarget<Color = BinaryColor>>(display: &mut D, cx: i32, cy: i32, radius: u32, filled: bool) -> Result<Rectangle, DisplayError> {
+    let area = display.bounding_box();
+    let mut clipped = display.clipped(&area);
+
+    let diameter = radius.saturating_mul(2).saturating_add(1);
+    let circle = Circle::with_center(Point::new(cx, cy), diameter);
+
+    if filled {
+        circle.into_styled(PrimitiveStyle::with_fill(BinaryColor::On))
+            .draw(&mut clipped)
+            .map_err(|_| DisplayError::DrawError)?;
+    } else {
+        circle.into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+            .draw(&mut clipped)
+            .map_err(|_| DisplayError::DrawError)?;
+    }
+
+    Ok(circle.bounding_box().intersection(&area))
+}
+
+pub(crate) fn render_bitmap<D: DrawTarget<Color = BinaryColor>>(display: &mut D, x: i32, y: i32, width: u32, data: &[u8]) -> Result<Rectangle, DisplayError> {
+    let bytes_per_row = width.div_ceil(8) as usize;
+
+    //rows are padded to whole bytes, anything else isn't a valid 1bpp image
+    if width == 0 || data.is_empty() || data.len() % bytes_per_row != 0 {
+        return Err(DisplayError::DrawError);
+    }
+
+    let area = display.bounding_box();
+    let mut clipped = display.clipped(&area);
+
+    let raw = ImageRaw::<BinaryColor>::new(data, width);
+    let image = Image::new(&raw, Point::new(x, y));
+
+    image.draw(&mut clipped)
+        .map_err(|_| DisplayError::DrawError)?;
+
+    Ok(image.bounding_box().intersection(&area))
+}
+
+fn font_for(size: TextSize) -> &'static MonoFont<'static> {
+    match size {
+        TextSize::Small => &FONT_5X8,
+        TextSize::Normal => &FONT_6X10,
+        TextSize::Large => &FONT_9X15,
+    }
+}
+
+/// `(char_width, line_height)` in pixels for the font `size` is drawn with. Everything that
+/// measures or centres text goes through this so it can't drift from what's actually drawn.
+pub fn font_metrics(size: TextSize) -> (u32, u32) {
+    let font = font_for(size);
+    (font.character_size.width + font.character_spacing, font.character_size.height)
+}
+
+//monospace, so this is exact for ascii. the widest line for multi-line text
+pub fn text_width(text: &str, size: TextSize) -> u32 {
+    measure_text(text, size).0
+}
+
+/// `(width, height)` the text takes up when drawn at `size`, without drawing it. Lines are
+/// stacked at the font's line height, the same way `draw_text` lays them out. Empty text
+/// still measures one line high so labels keep their row.
+pub fn measure_text(text: &str, size: TextSize) -> (u32, u32) {
+    let (char_width, line_height) = font_metrics(size);
+
+    let (widest, lines) = text.lines().fold((0, 0), |(widest, lines), line| {
+        (widest.max(line.chars().count() as u32), lines + 1)
+    });
+
+    (widest * char_width, lines.max(1) * line_height)
+}
+
+//rows padded to whole bytes, msb first, which is what ImageRaw and render_bitmap expect
+#[cfg(any(target_os = "espidf", feature = "simulator"))]
+pub(crate) fn pack_pixels<F: Fn(Point) -> bool>(size: Size, pixel: F) -> Vec<u8> {
+    let bytes_per_row = size.width.div_ceil(8) as usize;
+    let mut data = vec![0; bytes_per_row * size.height as usize];
+
+    for y in 0..size.height as usize {
+        for x in 0..size.width as usize {
+            if pixel(Point::new(x as i32, y as i32)) {
+                data[y * bytes_per_row + x / 8] |= 0x80 >> (x % 8);
+            }
+        }
+    }
+
+    data
+}
+
+pub(crate) fn union_rects(a: &Rectangle, b: &Rectangle) -> Rectangle {
+    let left = a.top_left.x.min(b.top_left.x);
+    let top = a.top_left.y.min(b.top_left.y);
+    let right = (a.top_left.x + a.size.width as i32).max(b.top_left.x + b.size.width as i32);
+    let bottom = (a.top_left.y + a.size.height as i32).max(b.top_left.y + b.size.height as i32);
+
+    Rectangle::new(Point::new(left, top), Size::new((right - left) as u32, (bottom - top) as u32))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TextSize {
     Small,
     Normal,
     Large
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn framebuffer() -> Framebuffer {
+        Framebuffer::new(Size::new(128, 64))
+    }
+
+    fn row_lit(frame: &Framebuffer, y: i32) -> bool {
+        (0..128).any(|x| frame.get(Point::new(x, y)))
+    }
+
+    #[test]
+    fn second_line_is_the_first_moved_down_one_line_height() {
+        let mut frame = framebuffer();
+        let (_, line_height) = font_metrics(TextSize::Normal);
+
+        let area = render_text(&mut frame, "Ag\nAg", 0, 0, TextSize::Normal, None).unwrap();
+
+        assert_eq!(area.size.height, line_height * 2);
+        assert!(row_lit(&frame, 2));
+        for y in 0..line_height as i32 {
+            for x in 0..20 {
+                assert_eq!(frame.get(Point::new(x, y)), frame.get(Point::new(x, y + line_height as i32)), "pixel ({}, {})", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn trailing_newline_draws_no_extra_line() {
+        let mut plain = framebuffer();
+        let mut trailing = framebuffer();
+        let (_, line_height) = font_metrics(TextSize::Normal);
+
+        let plain_area = render_text(&mut plain, "Ag", 0, 0, TextSize::Normal, None).unwrap();
+        let trailing_area = render_text(&mut trailing, "Ag\n", 0, 0, TextSize::Normal, None).unwrap();
+
+        assert_eq!(trailing_area, plain_area);
+        assert_eq!(trailing_area.size.height, line_height);
+        assert!((line_height as i32..64).all(|y| !row_lit(&trailing, y)));
+    }
+
+    #[test]
+    fn font_metrics_per_size() {
+        assert_eq!(font_metrics(TextSize::Small), (5, 8));
+        assert_eq!(font_metrics(TextSize::Normal), (6, 10));
+        assert_eq!(font_metrics(TextSize::Large), (9, 15));
+    }
+
+    #[test]
+    fn measure_text_is_one_advance_per_char() {
+        for size in [TextSize::Small, TextSize::Normal, TextSize::Large] {
+            let (advance, line_height) = font_metrics(size);
+            assert_eq!(measure_text("Hello", size), (5 * advance, line_height));
+            assert_eq!(measure_text("", size), (0, line_height));
+            assert_eq!(measure_text("ab\nabcd", size), (4 * advance, 2 * line_height));
+        }
+    }
+
+    #[test]
+    fn drawn_text_covers_what_measure_text_says() {
+        for size in [TextSize::Small, TextSize::Normal, TextSize::Large] {
+            let mut plain = framebuffer();
+            let mut inverted = framebuffer();
+            let (width, height) = measure_text("Hi", size);
+
+            let drawn = render_text(&mut plain, "Hi", 4, 4, size, None).unwrap();
+            let highlight = render_text_inverted(&mut inverted, "Hi", 4, 4, size).unwrap();
+
+            assert_eq!(drawn.size, Size::new(width, height));
+            assert_eq!(highlight, Rectangle::new(Point::new(4, 4), Size::new(width, height)));
+
+            //the highlight's last column is still lit, the one after it isn't
+            assert!(inverted.get(Point::new(4 + width as i32 - 1, 4)));
+            assert!(!inverted.get(Point::new(4 + width as i32, 4)));
+        }
+    }
+}
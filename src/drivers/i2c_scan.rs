@@ -0,0 +1,28 @@
+use crate::drivers::display::DEFAULT_ADDRESS;
+use esp_idf_hal::delay::TickType;
+use esp_idf_hal::i2c::I2cDriver;
+
+//0x00-0x02 and 0x78-0x7F are reserved by the i2c spec, nothing real answers there
+const FIRST_ADDRESS: u8 = 0x03;
+const LAST_ADDRESS: u8 = 0x77;
+const PROBE_TIMEOUT_MS: u64 = 10;
+
+//ssd1306 modules only ever come strapped to one of these two
+const SSD1306_ADDRESSES: [u8; 2] = [DEFAULT_ADDRESS, 0x3D];
+
+pub struct I2cScanner;
+
+impl I2cScanner {
+    //an empty write is just the address byte, anything that acks it is on the bus
+    pub fn scan(i2c: &mut I2cDriver<'_>) -> Vec<u8> {
+        let timeout = TickType::new_millis(PROBE_TIMEOUT_MS).ticks();
+
+        (FIRST_ADDRESS..=LAST_ADDRESS)
+            .filter(|&address| i2c.write(address, &[], timeout).is_ok())
+            .collect()
+    }
+
+    pub fn find_ssd1306(addresses: &[u8]) -> Option<u8> {
+        SSD1306_ADDRESSES.iter().copied().find(|address| addresses.contains(address))
+    }
+}
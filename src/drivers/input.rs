@@ -1,10 +1,22 @@
+#[cfg(target_os = "espidf")]
 use esp_idf_hal::{
-    gpio::{AnyIOPin, Input, Pin, PinDriver},
+    gpio::{AnyIOPin, Input, InterruptType, Pin, PinDriver},
     prelude::*,
 };
+#[cfg(target_os = "espidf")]
 use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+#[cfg(target_os = "espidf")]
+use std::sync::atomic::{AtomicU64, Ordering};
+#[cfg(target_os = "espidf")]
 use std::sync::{Arc, Mutex};
-use crate::system::events::{Event, EventQueue};
+use std::time::{Duration, Instant};
+#[cfg(target_os = "espidf")]
+use crate::system::events::{AnyButtonEventSource, Event, EventQueue};
+
+//same window ButtonEventSource uses by default
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(50);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ButtonState {
@@ -12,82 +24,298 @@ pub enum ButtonState {
     Released,
 }
 
+/// What a single reading does to a debounced button, see [`debounce`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DebounceOutcome {
+    //the reading agrees with the current state
+    Steady,
+    //a new state, report it and restart the window from `now`
+    Changed(ButtonState),
+    //differs, but the last change is still inside the window. read the pin again once it has passed
+    Settling,
+}
+
+/// Debounces one reading of an active-low pin. `state` is the current debounced state and
+/// when it last changed. Anything that differs inside `window` after a change is taken as
+/// contact bounce, but it isn't thrown away: the caller has to sample again later, or a tap
+/// that's released inside the window would never report its release.
+pub fn debounce(state: (ButtonState, Instant), level: bool, now: Instant, window: Duration) -> DebounceOutcome {
+    let (current, changed_at) = state;
+    let reading = if level { ButtonState::Released } else { ButtonState::Pressed };
+
+    if reading == current {
+        DebounceOutcome::Steady
+    } else if now.duration_since(changed_at) >= window {
+        DebounceOutcome::Changed(reading)
+    } else {
+        DebounceOutcome::Settling
+    }
+}
+
 #[derive(Debug)]
 pub enum InputError {
     GpioError,
     NotInitialized,
 }
 
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::GpioError => write!(f, "GPIO configuration error"),
+            InputError::NotInitialized => write!(f, "Button not registered"),
+        }
+    }
+}
+
+impl Error for InputError {}
+
+#[cfg(target_os = "espidf")]
+#[deprecated(note = "use ButtonPoller with AnyButtonEventSource instead")]
 pub struct InputManager {
-    button_states: Arc<Mutex<HashMap<u32, ButtonState>>>,
+    //state plus when it last changed, for the debounce lockout
+    button_states: Arc<Mutex<HashMap<u32, (ButtonState, Instant)>>>,
+    debounce: Duration,
     event_queue: Arc<EventQueue>,
+    interrupt_pins: Mutex<Vec<(PinDriver<'static, AnyIOPin, Input>, u32)>>,
+    //one bit per gpio number, set from the isr and drained by process_interrupts
+    pending: Arc<AtomicU64>,
 }
 
+#[cfg(target_os = "espidf")]
+#[allow(deprecated)]
 impl InputManager {
     pub fn new(event_queue: Arc<EventQueue>) -> Self {
         Self {
             button_states: Arc::new(Mutex::new(HashMap::new())),
+            debounce: DEFAULT_DEBOUNCE,
             event_queue,
+            interrupt_pins: Mutex::new(Vec::new()),
+            pending: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    pub fn with_debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    pub fn add_interrupt_button(&self, pin: PinDriver<'static, AnyIOPin, Input>, pin_number: u32) -> Result<(), InputError> {
+        if pin_number >= 64 {
+            return Err(InputError::GpioError);
+        }
+
+        self.register_button(&pin, pin_number)?;
+        self.interrupt_pins.lock().unwrap().push((pin, pin_number));
+        Ok(())
+    }
+
+    //the isr can't take a mutex or touch the event queue, it only flags the pin, so process_interrupts
+    //still has to be called from the main loop, but it's a single atomic swap when nothing happened
+    pub fn enable_interrupts(&self) -> Result<(), InputError> {
+        let mut pins = self.interrupt_pins.lock().unwrap();
+
+        for (pin, pin_number) in pins.iter_mut() {
+            let pending = self.pending.clone();
+            let bit = 1u64 << *pin_number;
+
+            pin.set_interrupt_type(InterruptType::AnyEdge)
+                .map_err(|_| InputError::GpioError)?;
+
+            //safety: the callback only does an atomic or, which is fine in isr context
+            unsafe {
+                pin.subscribe(move || {
+                    pending.fetch_or(bit, Ordering::Relaxed);
+                })
+                .map_err(|_| InputError::GpioError)?;
+            }
+
+            pin.enable_interrupt().map_err(|_| InputError::GpioError)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn process_interrupts(&self) -> Result<(), InputError> {
+        let pending = self.pending.swap(0, Ordering::Relaxed);
+        if pending == 0 {
+            return Ok(());
+        }
+
+        let mut pins = self.interrupt_pins.lock().unwrap();
+
+        for (pin, pin_number) in pins.iter_mut() {
+            if pending & (1u64 << *pin_number) == 0 {
+                continue;
+            }
+
+            //the level is read now rather than in the isr, bounces in between collapse into the final state
+            let outcome = self.update_button_state(*pin_number, pin.is_high())?;
+
+            //no further edge may come (a quick tap's release can land inside the window), so keep
+            //the pin flagged and read it again on the next call until the window has passed
+            if outcome == DebounceOutcome::Settling {
+                self.pending.fetch_or(1u64 << *pin_number, Ordering::Relaxed);
+            }
+
+            //esp-idf disarms the interrupt after every trigger
+            pin.enable_interrupt().map_err(|_| InputError::GpioError)?;
         }
+
+        Ok(())
     }
 
     pub fn register_button<P: Pin>(&self, pin: &PinDriver<'_, P, Input>, pin_number: u32) -> Result<(), InputError> {
         let mut states = self.button_states.lock().unwrap();
-        states.insert(pin_number, if pin.is_high() {ButtonState::Released} else {ButtonState::Pressed});
+        states.insert(pin_number, (if pin.is_high() {ButtonState::Released} else {ButtonState::Pressed}, Instant::now()));
         Ok(())
     }
 
-    pub fn update_button_state(&self, pin_number: u32, state: bool) -> Result<(), InputError> {
+    pub fn update_button_state(&self, pin_number: u32, state: bool) -> Result<DebounceOutcome, InputError> {
         let mut states = self.button_states.lock().unwrap();
+        let current = *states.get(&pin_number).ok_or(InputError::NotInitialized)?;
+        let now = Instant::now();
 
-        if let Some(current_state) = states.get(&pin_number) {
-            let new_state = if state {ButtonState::Released} else {ButtonState::Pressed};
-
-            if new_state != *current_state {
-                match new_state {
-                    ButtonState::Pressed => {
-                        self.event_queue.push(Event::ButtonPressed(pin_number));
-                    },
-                    ButtonState::Released => {
-                        self.event_queue.push(Event::ButtonReleased(pin_number));
-                    },
-                }
+        let outcome = debounce(current, state, now, self.debounce);
 
-                states.insert(pin_number, new_state);
+        if let DebounceOutcome::Changed(new_state) = outcome {
+            match new_state {
+                ButtonState::Pressed => {
+                    self.event_queue.push(Event::ButtonPressed(pin_number));
+                },
+                ButtonState::Released => {
+                    self.event_queue.push(Event::ButtonReleased(pin_number));
+                },
             }
+
+            states.insert(pin_number, (new_state, now));
         }
 
-        Ok(())
+        Ok(outcome)
     }
 
     pub fn get_button_state(&self, pin_number: u32) -> Result<ButtonState, InputError> {
         let states = self.button_states.lock().unwrap();
-        states.get(&pin_number).copied().ok_or(InputError::NotInitialized)
+        states.get(&pin_number).map(|(state, _)| *state).ok_or(InputError::NotInitialized)
     }
 }
 
+#[cfg(target_os = "espidf")]
+/// The one place buttons get polled. Each source does its own debouncing and gestures,
+/// the poller just keeps them together and answers state queries by gpio number.
 pub struct ButtonPoller<'a> {
-    input_manager: Arc<InputManager>,
-    buttons: Vec<(PinDriver<'a, AnyIOPin, Input>, u32)>,
+    buttons: Vec<AnyButtonEventSource<'a>>,
 }
 
+#[cfg(target_os = "espidf")]
 impl<'a> ButtonPoller<'a> {
-    pub fn new(input_manager: Arc<InputManager>) -> Self {
+    pub fn new() -> Self {
         Self {
-            input_manager,
             buttons: Vec::new(),
         }
     }
 
-    pub fn add_button(&mut self, pin: PinDriver<'a, AnyIOPin, Input>, pin_number: u32) {
-        let _ = self.input_manager.register_button(&pin, pin_number);
-        self.buttons.push((pin, pin_number));
+    pub fn with_button(mut self, source: AnyButtonEventSource<'a>) -> Self {
+        self.add_button(source);
+        self
+    }
+
+    //a second source on the same gpio replaces the first rather than double-reporting it
+    pub fn add_button(&mut self, source: AnyButtonEventSource<'a>) {
+        self.buttons.retain(|button| button.pin_number() != source.pin_number());
+        self.buttons.push(source);
     }
 
     pub fn poll(&mut self) {
-        for (pin, pin_number) in &self.buttons {
-            let _ = self.input_manager.update_button_state(*pin_number, pin.is_high());
+        for button in &mut self.buttons {
+            button.poll();
         }
     }
+
+    pub fn get_button_state(&self, pin_number: u32) -> Result<ButtonState, InputError> {
+        self.buttons.iter()
+            .find(|button| button.pin_number() == pin_number)
+            .map(|button| button.state())
+            .ok_or(InputError::NotInitialized)
+    }
 }
 
+#[cfg(target_os = "espidf")]
+impl Default for ButtonPoller<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW: Duration = Duration::from_millis(50);
+
+    //feeds (ms, level) readings through debounce the way InputManager does, returns the reported states
+    fn run(start: ButtonState, readings: &[(u64, bool)]) -> Vec<(u64, ButtonState)> {
+        let origin = Instant::now();
+        let mut state = (start, origin);
+        let mut reported = Vec::new();
+
+        for &(ms, level) in readings {
+            let now = origin + Duration::from_millis(ms);
+            if let DebounceOutcome::Changed(new_state) = debounce(state, level, now, WINDOW) {
+                state = (new_state, now);
+                reported.push((ms, new_state));
+            }
+        }
+
+        reported
+    }
+
+    #[test]
+    fn steady_reading_reports_nothing() {
+        let origin = Instant::now();
+        let outcome = debounce((ButtonState::Released, origin), true, origin + WINDOW * 2, WINDOW);
+        assert_eq!(outcome, DebounceOutcome::Steady);
+    }
+
+    #[test]
+    fn bounce_after_a_press_is_ignored() {
+        //idle long enough for the first edge to count, then the contacts chatter for 10ms
+        let reported = run(ButtonState::Released, &[
+            (100, false),
+            (102, true),
+            (104, false),
+            (107, true),
+            (110, false),
+            (160, false),
+        ]);
+
+        assert_eq!(reported, vec![(100, ButtonState::Pressed)]);
+    }
+
+    #[test]
+    fn release_inside_the_window_settles_instead_of_being_lost() {
+        let origin = Instant::now();
+        let pressed = (ButtonState::Pressed, origin);
+
+        //released 20ms after the press: too soon to report, but flagged to be read again
+        let early = debounce(pressed, true, origin + Duration::from_millis(20), WINDOW);
+        assert_eq!(early, DebounceOutcome::Settling);
+
+        //the re-sample once the window has passed picks the release up
+        let late = debounce(pressed, true, origin + WINDOW, WINDOW);
+        assert_eq!(late, DebounceOutcome::Changed(ButtonState::Released));
+    }
+
+    #[test]
+    fn quick_tap_reports_press_and_release() {
+        //the release edge at 120 lands inside the window, the re-sample at 150 reports it
+        let reported = run(ButtonState::Released, &[
+            (100, false),
+            (103, true),
+            (105, false),
+            (120, true),
+            (150, true),
+        ]);
+
+        assert_eq!(reported, vec![(100, ButtonState::Pressed), (150, ButtonState::Released)]);
+    }
+}
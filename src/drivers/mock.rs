@@ -0,0 +1,180 @@
+use crate::drivers::display::{Canvas, DisplayBackend, DisplayError, TextSize, DEFAULT_CONTRAST};
+use ssd1306::prelude::DisplayRotation;
+use std::sync::{Mutex, PoisonError};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DrawCall {
+    Clear,
+    Flush,
+    FlushDirty,
+    Text { text: String, x: i32, y: i32, size: TextSize },
+    TextClipped { text: String, x: i32, y: i32, size: TextSize, clip_x: i32, clip_width: u32 },
+    TextInverted { text: String, x: i32, y: i32, size: TextSize },
+    Rectangle { x: i32, y: i32, width: u32, height: u32, filled: bool },
+    RoundedRectangle { x: i32, y: i32, width: u32, height: u32, radius: u32, filled: bool },
+    Line { x0: i32, y0: i32, x1: i32, y1: i32 },
+    Circle { cx: i32, cy: i32, radius: u32, filled: bool },
+    Bitmap { x: i32, y: i32, width: u32, len: usize },
+    ClearRegion { x: i32, y: i32, width: u32, height: u32 },
+    ProgressBar { x: i32, y: i32, width: u32, progress: u8 },
+    Contrast(u8),
+    DisplayOn(bool),
+    Rotation,
+}
+
+//records every call instead of drawing, for asserting what a screen asked the display to do
+pub struct MockDisplay {
+    width: u32,
+    height: u32,
+    calls: Mutex<Vec<DrawCall>>,
+    contrast: Mutex<u8>,
+}
+
+impl MockDisplay {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            calls: Mutex::new(Vec::new()),
+            contrast: Mutex::new(DEFAULT_CONTRAST),
+        }
+    }
+
+    fn record(&self, call: DrawCall) {
+        self.calls.lock().unwrap_or_else(PoisonError::into_inner).push(call);
+    }
+
+    pub fn calls(&self) -> Vec<DrawCall> {
+        self.calls.lock().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    //hands back everything recorded so far and starts a fresh log
+    pub fn take_calls(&self) -> Vec<DrawCall> {
+        std::mem::take(&mut *self.calls.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    pub fn count<F>(&self, predicate: F) -> usize
+    where
+        F: Fn(&DrawCall) -> bool,
+    {
+        self.calls.lock().unwrap_or_else(PoisonError::into_inner).iter().filter(|call| predicate(call)).count()
+    }
+
+    //a redraw is anything that pushed a frame out, full or partial
+    pub fn redraw_count(&self) -> usize {
+        self.count(|call| matches!(call, DrawCall::Flush | DrawCall::FlushDirty))
+    }
+
+    pub fn drew_text(&self, text: &str) -> bool {
+        self.count(|call| match call {
+            DrawCall::Text { text: drawn, .. }
+            | DrawCall::TextClipped { text: drawn, .. }
+            | DrawCall::TextInverted { text: drawn, .. } => drawn == text,
+            _ => false,
+        }) > 0
+    }
+}
+
+impl Canvas for MockDisplay {
+    fn clear(&self) -> Result<(), DisplayError> {
+        self.record(DrawCall::Clear);
+        Ok(())
+    }
+
+    fn draw_text(&self, text: &str, x: i32, y: i32, size: TextSize) -> Result<(), DisplayError> {
+        self.record(DrawCall::Text { text: text.to_string(), x, y, size });
+        Ok(())
+    }
+
+    fn draw_text_clipped(&self, text: &str, x: i32, y: i32, size: TextSize, clip_x: i32, clip_width: u32) -> Result<(), DisplayError> {
+        self.record(DrawCall::TextClipped { text: text.to_string(), x, y, size, clip_x, clip_width });
+        Ok(())
+    }
+
+    fn draw_text_inverted(&self, text: &str, x: i32, y: i32, size: TextSize) -> Result<(), DisplayError> {
+        self.record(DrawCall::TextInverted { text: text.to_string(), x, y, size });
+        Ok(())
+    }
+
+    fn draw_rectangle(&self, x: i32, y: i32, width: u32, height: u32, filled: bool) -> Result<(), DisplayError> {
+        self.record(DrawCall::Rectangle { x, y, width, height, filled });
+        Ok(())
+    }
+
+    fn draw_rounded_rectangle(&self, x: i32, y: i32, width: u32, height: u32, radius: u32, filled: bool) -> Result<(), DisplayError> {
+        self.record(DrawCall::RoundedRectangle { x, y, width, height, radius, filled });
+        Ok(())
+    }
+
+    fn draw_line(&self, x0: i32, y0: i32, x1: i32, y1: i32) -> Result<(), DisplayError> {
+        self.record(DrawCall::Line { x0, y0, x1, y1 });
+        Ok(())
+    }
+
+    fn draw_circle(&self, cx: i32, cy: i32, radius: u32, filled: bool) -> Result<(), DisplayError> {
+        self.record(DrawCall::Circle { cx, cy, radius, filled });
+        Ok(())
+    }
+
+    fn draw_bitmap(&self, x: i32, y: i32, width: u32, data: &[u8]) -> Result<(), DisplayError> {
+        self.record(DrawCall::Bitmap { x, y, width, len: data.len() });
+        Ok(())
+    }
+
+    fn clear_region(&self, x: i32, y: i32, width: u32, height: u32) -> Result<(), DisplayError> {
+        self.record(DrawCall::ClearRegion { x, y, width, height });
+        Ok(())
+    }
+
+    fn width(&self) -> u32 {
+        self.width
+    }
+
+    fn height(&self) -> u32 {
+        self.height
+    }
+
+    //recorded as one call rather than the rectangles the default draws it with
+    fn draw_progress_bar(&self, x: i32, y: i32, width: u32, progress: u8) -> Result<(), DisplayError> {
+        self.record(DrawCall::ProgressBar { x, y, width, progress });
+        Ok(())
+    }
+}
+
+impl DisplayBackend for MockDisplay {
+    fn flush(&self) -> Result<(), DisplayError> {
+        self.record(DrawCall::Flush);
+        Ok(())
+    }
+
+    fn flush_dirty(&self) -> Result<(), DisplayError> {
+        self.record(DrawCall::FlushDirty);
+        Ok(())
+    }
+
+    fn draw_frame(&self, f: &mut dyn FnMut(&dyn Canvas) -> Result<(), DisplayError>) -> Result<(), DisplayError> {
+        f(self)?;
+        self.flush_dirty()
+    }
+
+    fn set_contrast(&self, value: u8) -> Result<(), DisplayError> {
+        *self.contrast.lock().unwrap_or_else(PoisonError::into_inner) = value;
+        self.record(DrawCall::Contrast(value));
+        Ok(())
+    }
+
+    fn contrast(&self) -> u8 {
+        *self.contrast.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn set_display_on(&self, on: bool) -> Result<(), DisplayError> {
+        self.record(DrawCall::DisplayOn(on));
+        Ok(())
+    }
+
+    fn set_rotation(&self, _rotation: DisplayRotation) -> Result<(), DisplayError> {
+        //DisplayRotation has no PartialEq to compare against, so only note that it happened
+        self.record(DrawCall::Rotation);
+        Ok(())
+    }
+}
@@ -1,2 +1,10 @@
 pub mod display;
 pub mod input;
+#[cfg(target_os = "espidf")]
+pub mod battery;
+#[cfg(target_os = "espidf")]
+pub mod i2c_scan;
+#[cfg(feature = "simulator")]
+pub mod simulator;
+#[cfg(feature = "simulator")]
+pub mod mock;
@@ -0,0 +1,176 @@
+use crate::drivers::display::{
+    clear_panel, pack_pixels, render_bitmap, render_circle, render_clear_region, render_line, render_rectangle,
+    render_rounded_rectangle, render_text, render_text_inverted, Canvas, DisplayBackend, DisplayError, Framebuffer,
+    TextSize, DEFAULT_CONTRAST,
+};
+use embedded_graphics::prelude::{OriginDimensions, Point, Size};
+use ssd1306::prelude::DisplayRotation;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+//in-memory stand-in for the ssd1306, renders with the same helpers so screens can be checked pixel by pixel on the host
+pub struct SimulatorDisplay {
+    frame: Mutex<Framebuffer>,
+    contrast: Mutex<u8>,
+    display_on: Mutex<bool>,
+    rotation: Mutex<DisplayRotation>,
+    flushes: Mutex<u32>,
+    flush_held: Mutex<bool>,
+}
+
+impl SimulatorDisplay {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            frame: Mutex::new(Framebuffer::new(Size::new(width, height))),
+            contrast: Mutex::new(DEFAULT_CONTRAST),
+            display_on: Mutex::new(true),
+            rotation: Mutex::new(DisplayRotation::Rotate0),
+            flushes: Mutex::new(0),
+            flush_held: Mutex::new(false),
+        }
+    }
+
+    fn lock_frame(&self) -> MutexGuard<'_, Framebuffer> {
+        self.frame.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn get_pixel(&self, x: i32, y: i32) -> bool {
+        self.lock_frame().get(Point::new(x, y))
+    }
+
+    pub fn lit_pixels(&self) -> usize {
+        let frame = self.lock_frame();
+        let size = frame.size();
+
+        (0..size.height as i32)
+            .flat_map(|y| (0..size.width as i32).map(move |x| Point::new(x, y)))
+            .filter(|point| frame.get(*point))
+            .count()
+    }
+
+    //one line per row, '#' for lit pixels, handy for eyeballing a failed assertion
+    pub fn to_ascii(&self) -> String {
+        let frame = self.lock_frame();
+        let size = frame.size();
+        let mut out = String::with_capacity(((size.width + 1) * size.height) as usize);
+
+        for y in 0..size.height as i32 {
+            for x in 0..size.width as i32 {
+                out.push(if frame.get(Point::new(x, y)) { '#' } else { '.' });
+            }
+            out.push('\n');
+        }
+
+        out
+    }
+
+    pub fn flush_count(&self) -> u32 {
+        *self.flushes.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn is_display_on(&self) -> bool {
+        *self.display_on.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn rotation(&self) -> DisplayRotation {
+        *self.rotation.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Canvas for SimulatorDisplay {
+    fn clear(&self) -> Result<(), DisplayError> {
+        clear_panel(&mut *self.lock_frame()).map(|_| ())
+    }
+
+    fn draw_text(&self, text: &str, x: i32, y: i32, size: TextSize) -> Result<(), DisplayError> {
+        render_text(&mut *self.lock_frame(), text, x, y, size, None).map(|_| ())
+    }
+
+    fn draw_text_clipped(&self, text: &str, x: i32, y: i32, size: TextSize, clip_x: i32, clip_width: u32) -> Result<(), DisplayError> {
+        render_text(&mut *self.lock_frame(), text, x, y, size, Some((clip_x, clip_width))).map(|_| ())
+    }
+
+    fn draw_text_inverted(&self, text: &str, x: i32, y: i32, size: TextSize) -> Result<(), DisplayError> {
+        render_text_inverted(&mut *self.lock_frame(), text, x, y, size).map(|_| ())
+    }
+
+    fn draw_rectangle(&self, x: i32, y: i32, width: u32, height: u32, filled: bool) -> Result<(), DisplayError> {
+        render_rectangle(&mut *self.lock_frame(), x, y, width, height, filled).map(|_| ())
+    }
+
+    fn draw_rounded_rectangle(&self, x: i32, y: i32, width: u32, height: u32, radius: u32, filled: bool) -> Result<(), DisplayError> {
+        render_rounded_rectangle(&mut *self.lock_frame(), x, y, width, height, radius, filled).map(|_| ())
+    }
+
+    fn draw_line(&self, x0: i32, y0: i32, x1: i32, y1: i32) -> Result<(), DisplayError> {
+        render_line(&mut *self.lock_frame(), x0, y0, x1, y1).map(|_| ())
+    }
+
+    fn draw_circle(&self, cx: i32, cy: i32, radius: u32, filled: bool) -> Result<(), DisplayError> {
+        render_circle(&mut *self.lock_frame(), cx, cy, radius, filled).map(|_| ())
+    }
+
+    fn draw_bitmap(&self, x: i32, y: i32, width: u32, data: &[u8]) -> Result<(), DisplayError> {
+        render_bitmap(&mut *self.lock_frame(), x, y, width, data).map(|_| ())
+    }
+
+    fn clear_region(&self, x: i32, y: i32, width: u32, height: u32) -> Result<(), DisplayError> {
+        render_clear_region(&mut *self.lock_frame(), x, y, width, height).map(|_| ())
+    }
+
+    fn width(&self) -> u32 {
+        self.lock_frame().size().width
+    }
+
+    fn height(&self) -> u32 {
+        self.lock_frame().size().height
+    }
+}
+
+impl DisplayBackend for SimulatorDisplay {
+    //there's no panel to push to, flushing only gets counted
+    fn flush(&self) -> Result<(), DisplayError> {
+        if *self.flush_held.lock().unwrap_or_else(PoisonError::into_inner) {
+            return Ok(());
+        }
+
+        *self.flushes.lock().unwrap_or_else(PoisonError::into_inner) += 1;
+        Ok(())
+    }
+
+    fn flush_dirty(&self) -> Result<(), DisplayError> {
+        self.flush()
+    }
+
+    fn draw_frame(&self, f: &mut dyn FnMut(&dyn Canvas) -> Result<(), DisplayError>) -> Result<(), DisplayError> {
+        f(self)?;
+        self.flush_dirty()
+    }
+
+    fn set_contrast(&self, value: u8) -> Result<(), DisplayError> {
+        *self.contrast.lock().unwrap_or_else(PoisonError::into_inner) = value;
+        Ok(())
+    }
+
+    fn contrast(&self) -> u8 {
+        *self.contrast.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn set_display_on(&self, on: bool) -> Result<(), DisplayError> {
+        *self.display_on.lock().unwrap_or_else(PoisonError::into_inner) = on;
+        Ok(())
+    }
+
+    fn set_rotation(&self, rotation: DisplayRotation) -> Result<(), DisplayError> {
+        *self.rotation.lock().unwrap_or_else(PoisonError::into_inner) = rotation;
+        Ok(())
+    }
+
+    fn capture(&self) -> Option<Vec<u8>> {
+        let frame = self.lock_frame();
+        Some(pack_pixels(frame.size(), |point| frame.get(point)))
+    }
+
+    fn hold_flush(&self, hold: bool) {
+        *self.flush_held.lock().unwrap_or_else(PoisonError::into_inner) = hold;
+    }
+}
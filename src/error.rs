@@ -0,0 +1,98 @@
+use crate::drivers::display::DisplayError;
+use crate::drivers::input::InputError;
+#[cfg(target_os = "espidf")]
+use crate::system::settings::SettingsError;
+#[cfg(target_os = "espidf")]
+use crate::system::wifi::WifiError;
+use crate::ui::layout::LayoutError;
+#[cfg(target_os = "espidf")]
+use esp_idf_sys::EspError;
+use std::error::Error;
+use std::fmt;
+
+/// Every subsystem error in one place, so anything can return `Result<T, OsError>` and
+/// `?` whatever it calls. The subsystem error is kept as is, match on it for details.
+#[derive(Debug)]
+pub enum OsError {
+    Display(DisplayError),
+    Input(InputError),
+    #[cfg(target_os = "espidf")]
+    Wifi(WifiError),
+    #[cfg(target_os = "espidf")]
+    Settings(SettingsError),
+    Layout(LayoutError),
+    //straight from an esp-idf call that isn't wrapped by one of the above
+    #[cfg(target_os = "espidf")]
+    Esp(EspError),
+}
+
+impl fmt::Display for OsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OsError::Display(e) => write!(f, "Display: {}", e),
+            OsError::Input(e) => write!(f, "Input: {}", e),
+            #[cfg(target_os = "espidf")]
+            OsError::Wifi(e) => write!(f, "WiFi: {}", e),
+            #[cfg(target_os = "espidf")]
+            OsError::Settings(e) => write!(f, "Settings: {}", e),
+            OsError::Layout(e) => write!(f, "Layout: {}", e),
+            #[cfg(target_os = "espidf")]
+            OsError::Esp(e) => write!(f, "ESP-IDF error: {}", e),
+        }
+    }
+}
+
+impl Error for OsError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            OsError::Display(e) => Some(e),
+            OsError::Input(e) => Some(e),
+            #[cfg(target_os = "espidf")]
+            OsError::Wifi(e) => Some(e),
+            #[cfg(target_os = "espidf")]
+            OsError::Settings(e) => Some(e),
+            OsError::Layout(e) => Some(e),
+            #[cfg(target_os = "espidf")]
+            OsError::Esp(e) => Some(e),
+        }
+    }
+}
+
+impl From<DisplayError> for OsError {
+    fn from(error: DisplayError) -> Self {
+        OsError::Display(error)
+    }
+}
+
+impl From<InputError> for OsError {
+    fn from(error: InputError) -> Self {
+        OsError::Input(error)
+    }
+}
+
+#[cfg(target_os = "espidf")]
+impl From<WifiError> for OsError {
+    fn from(error: WifiError) -> Self {
+        OsError::Wifi(error)
+    }
+}
+
+#[cfg(target_os = "espidf")]
+impl From<SettingsError> for OsError {
+    fn from(error: SettingsError) -> Self {
+        OsError::Settings(error)
+    }
+}
+
+impl From<LayoutError> for OsError {
+    fn from(error: LayoutError) -> Self {
+        OsError::Layout(error)
+    }
+}
+
+#[cfg(target_os = "espidf")]
+impl From<EspError> for OsError {
+    fn from(error: EspError) -> Self {
+        OsError::Esp(error)
+    }
+}
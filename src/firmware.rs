@@ -0,0 +1,243 @@
+
+use esp_jarvis_handheld::drivers::display::{DisplayError, DisplayManager, DEFAULT_ADDRESS};
+use esp_jarvis_handheld::error::OsError;
+use esp_jarvis_handheld::drivers::i2c_scan::I2cScanner;
+use esp_jarvis_handheld::drivers::input::ButtonPoller;
+use esp_jarvis_handheld::ui::framework::ScreenManager;
+use esp_jarvis_handheld::ui::apps::ScreenApp;
+use esp_jarvis_handheld::ui::screens::loading::LoadingScreen;
+use esp_jarvis_handheld::ui::screens::home::HomeScreen;
+use esp_jarvis_handheld::ui::screens::icon_grid::IconGridScreen;
+use esp_jarvis_handheld::ui::icons;
+use esp_jarvis_handheld::ui::sprite::{Sprite, SpriteAnimation};
+use esp_jarvis_handheld::ui::screensaver::Screensaver;
+use esp_jarvis_handheld::ui::screens::settings::SettingsScreen;
+use esp_jarvis_handheld::ui::screens::system_info::SystemInfoScreen;
+use esp_jarvis_handheld::ui::screens::ScreenId;
+use esp_jarvis_handheld::system::events::{AnyButtonEventSource, ButtonMap, Event, EventQueue, SystemTickSource, TimerEventSource};
+use esp_jarvis_handheld::system::settings::SettingsStore;
+use esp_jarvis_handheld::system::wifi::WifiManager;
+use esp_jarvis_handheld::system::power::{IdleDimmer, PowerManager};
+use esp_jarvis_handheld::system::scheduler::Scheduler;
+use esp_jarvis_handheld::system::notifications::NotificationManager;
+use esp_jarvis_handheld::system::clock::SystemClock;
+#[cfg(feature = "event-log")]
+use esp_jarvis_handheld::system::event_log::EventLogger;
+
+use esp_idf_hal::{
+    delay::FreeRtos,
+    gpio::Pin,
+    i2c::{I2cConfig, I2cDriver},
+    prelude::*,
+};
+use esp_idf_svc::eventloop::EspSystemEventLoop;
+use esp_idf_svc::log::EspLogger;
+use esp_idf_svc::nvs::EspDefaultNvsPartition;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+//scheduler task ids count up from 0, keep hand-rolled timer ids well out of their way
+const CLOCK_TIMER_ID: u32 = 1000;
+
+fn complete_boot_step(screen_manager: &mut ScreenManager, name: &str) -> Result<(), DisplayError> {
+    match screen_manager.get_screen_as_mut::<LoadingScreen>() {
+        Some(screen) => screen.complete_step(name),
+        None => Ok(()),
+    }
+}
+
+pub fn run() -> Result<(), OsError> {
+    let system_clock = Arc::new(SystemClock::new());
+
+    EspLogger::initialize_default();
+    log::info!("Starting visionHubOS");
+
+    let peripherals = Peripherals::take()?;
+    let sysloop = EspSystemEventLoop::take()?;
+    let nvs_partition = EspDefaultNvsPartition::take()?;
+
+    let sda = peripherals.pins.gpio21;
+    let scl = peripherals.pins.gpio19;
+    let i2c = peripherals.i2c0;
+    let config = I2cConfig::new().baudrate(100.kHz().into());
+    let mut i2c_driver = I2cDriver::new(i2c, sda, scl, &config)?;
+
+    let devices = I2cScanner::scan(&mut i2c_driver);
+    for address in &devices {
+        log::info!("I2C device found at 0x{:02X}", address);
+    }
+
+    //fall back to the usual address so a failed scan still gets a proper init error
+    let display_address = I2cScanner::find_ssd1306(&devices).unwrap_or_else(|| {
+        log::warn!("No SSD1306 answered the scan, trying 0x{:02X}", DEFAULT_ADDRESS);
+        DEFAULT_ADDRESS
+    });
+
+    let display_manager = Arc::new(DisplayManager::new_with_address(i2c_driver, display_address)?);
+
+    let event_queue = EventQueue::new();
+    #[cfg(feature = "event-log")]
+    let event_queue = event_queue.with_logger(EventLogger::new().with_filter(|event| *event != Event::SystemTick));
+    let event_queue = Arc::new(event_queue);
+
+    //every screen sees logical actions, the wiring only lives here
+    let button_map = ButtonMap {
+        select: peripherals.pins.gpio26.pin() as u32,
+        scroll: peripherals.pins.gpio25.pin() as u32,
+        back: None,
+        encoder: None,
+    };
+
+    let mut buttons = ButtonPoller::new()
+        .with_button(
+            AnyButtonEventSource::from_pin(peripherals.pins.gpio25, event_queue.clone())?
+                .with_repeat(Duration::from_millis(500), Duration::from_millis(200)),
+        )
+        //a held select switches keyboard modes
+        .with_button(
+            AnyButtonEventSource::from_pin(peripherals.pins.gpio26, event_queue.clone())?
+                .with_long_press(Duration::from_millis(600)),
+        );
+
+    let mut loading_screen = LoadingScreen::new(display_manager.clone(), event_queue.clone(), "visionHubOS", "Booting...")
+        .with_logo(&icons::LOGO_OPEN, icons::LOGO_WIDTH, 8);
+
+    //weights are rough shares of boot time, joining wifi dwarfs everything else
+    loading_screen.add_step("Display", 1);
+    loading_screen.add_step("Settings", 1);
+    loading_screen.add_step("WiFi", 6);
+    loading_screen.add_step("UI", 2);
+
+    let mut screen_manager = ScreenManager::new(display_manager.clone(), event_queue.clone());
+    screen_manager.set_button_map(button_map);
+
+    screen_manager.add_screen_keyed(ScreenId::Loading, loading_screen);
+
+    screen_manager.switch_to_screen_by_key(ScreenId::Loading)?;
+
+    //the loading screen is up, so the display is too
+    complete_boot_step(&mut screen_manager, "Display")?;
+
+    let settings_store = match SettingsStore::new(nvs_partition.clone(), "visionhub") {
+        Ok(store) => Some(Arc::new(Mutex::new(store))),
+        Err(e) => {
+            log::error!("Settings unavailable, using defaults: {}", e);
+            None
+        }
+    };
+    complete_boot_step(&mut screen_manager, "Settings")?;
+
+    let scheduler = Arc::new(Mutex::new(Scheduler::new(event_queue.clone())));
+    screen_manager.set_scheduler(scheduler.clone());
+    let mut notifications = NotificationManager::new(display_manager.clone(), event_queue.clone());
+
+    //a radio that won't come up costs wifi, not the rest of the device
+    let mut wifi_manager = match WifiManager::new(peripherals.modem, sysloop, Some(nvs_partition), event_queue.clone()) {
+        Ok(wifi) => Some(wifi),
+        Err(e) => {
+            log::error!("WiFi unavailable: {}", e);
+            notifications.notify("WiFi unavailable", Duration::from_secs(3));
+            None
+        }
+    };
+
+    //credentials are provisioned into nvs, skip wifi entirely if there aren't any
+    if let (Some(store), Some(wifi_manager)) = (&settings_store, &mut wifi_manager) {
+        let store = store.lock().unwrap();
+        let ssid = store.get_str("wifi_ssid", "").unwrap_or_default();
+        let password = store.get_str("wifi_pass", "").unwrap_or_default();
+
+        if !ssid.is_empty() {
+            match wifi_manager.connect(&ssid, &password) {
+                Ok(_) => {
+                    notifications.notify("WiFi connected", Duration::from_secs(2));
+
+                    if let Err(e) = system_clock.sync_time() {
+                        log::warn!("Couldn't start SNTP: {}", e);
+                    }
+                },
+                Err(e) => {
+                    log::error!("WiFi connection failed: {}", e);
+                    notifications.notify("WiFi failed", Duration::from_secs(3));
+                }
+            }
+        }
+    }
+    //done either way, a failed or skipped connection isn't worth holding boot up for
+    complete_boot_step(&mut screen_manager, "WiFi")?;
+
+    let mut settings_screen = SettingsScreen::new(display_manager.clone(), event_queue.clone(), settings_store.clone());
+
+    //drives LoadingScreen and anything animated, at the rate saved in settings
+    let mut tick_source = SystemTickSource::new(Duration::from_millis(settings_screen.tick_interval_ms() as u64), event_queue.clone());
+    let tick_interval = tick_source.interval_handle();
+    settings_screen.set_on_tick_change(move |ms| *tick_interval.lock().unwrap() = Duration::from_millis(ms as u64));
+    let settings_index = screen_manager.add_screen_keyed(ScreenId::Settings, settings_screen);
+
+    let mut system_info_screen = SystemInfoScreen::new(display_manager.clone(), event_queue.clone(), system_clock.clone());
+    system_info_screen.set_refresh_timer(CLOCK_TIMER_ID);
+    let system_info_index = screen_manager.add_screen_keyed(ScreenId::SystemInfo, system_info_screen);
+
+    let mut home_screen = HomeScreen::new(display_manager.clone(), event_queue.clone(), system_clock.clone());
+    home_screen.set_menu_app("menu");
+    home_screen.set_clock_timer(CLOCK_TIMER_ID);
+    home_screen.set_settings_screen(settings_index);
+    screen_manager.add_screen_keyed(ScreenId::Home, home_screen);
+
+    let mut launcher = IconGridScreen::new(display_manager.clone(), "Apps", 3, 1);
+    let launcher_queue = event_queue.clone();
+    launcher.add_item("Settings", &icons::GEAR, icons::ICON_SIZE, move || launcher_queue.push(Event::NavigateTo(settings_index)));
+    let launcher_queue = event_queue.clone();
+    launcher.add_item("Info", &icons::INFO, icons::ICON_SIZE, move || launcher_queue.push(Event::NavigateTo(system_info_index)));
+    let launcher_queue = event_queue.clone();
+    launcher.add_item("Close", &icons::BACK, icons::ICON_SIZE, move || launcher_queue.push(Event::NavigateBack));
+    screen_manager.register_app(ScreenApp::new("menu", launcher));
+
+    //the loading screen announces when the last step is in, home takes over from there
+    screen_manager.register_global_shortcut(
+        |event| *event == Event::BootComplete,
+        |manager| manager.switch_to_screen_by_key(ScreenId::Home),
+    );
+    complete_boot_step(&mut screen_manager, "UI")?;
+    screen_manager.set_idle_dimmer(IdleDimmer::new(display_manager.clone(), Duration::from_secs(15), 0x08));
+
+    //4 frames x 16 bytes, cheap enough to keep resident for the whole session
+    let logo = Sprite::new(0, 0, icons::LOGO_WIDTH, 8, &[&icons::LOGO_OPEN, &icons::LOGO_OPEN, &icons::LOGO_OPEN, &icons::LOGO_BLINK])?;
+    let blink = SpriteAnimation::new(logo.frame_count(), 4);
+    screen_manager.set_screensaver(Screensaver::new(display_manager.clone(), logo, blink, Duration::from_secs(30)).with_dim_level(0x01));
+
+    let mut power_manager = PowerManager::new(display_manager.clone(), event_queue.clone(), Duration::from_secs(60));
+    power_manager.register_wake_pin(button_map.scroll as i32)?;
+    power_manager.register_wake_pin(button_map.select as i32)?;
+
+    let mut clock_source = TimerEventSource::new(CLOCK_TIMER_ID, Duration::from_secs(1), event_queue.clone());
+
+    let mut last_signal_poll = Instant::now();
+
+    loop {
+        buttons.poll();
+        clock_source.poll();
+        tick_source.poll();
+        power_manager.update();
+
+        if last_signal_poll.elapsed() >= Duration::from_secs(5) {
+            if let Some(rssi) = wifi_manager.as_ref().and_then(|wifi| wifi.rssi()) {
+                event_queue.push(Event::NetworkSignal(rssi));
+            }
+            last_signal_poll = Instant::now();
+        }
+
+        system_clock.update(&event_queue);
+
+        //shared with screens that schedule work, callbacks run with it locked so they mustn't reschedule
+        {
+            let mut scheduler = scheduler.lock().unwrap();
+            scheduler.update();
+            notifications.update(&mut scheduler);
+        }
+
+        screen_manager.process_events()?;
+
+        FreeRtos::delay_ms(10);
+    }
+}
@@ -0,0 +1,4 @@
+pub mod drivers;
+pub mod ui;
+pub mod system;
+pub mod error;
@@ -0,0 +1,138 @@
+use crate::system::events::{Event, EventQueue};
+#[cfg(target_os = "espidf")]
+use esp_idf_svc::sntp::{EspSntp, SyncStatus};
+#[cfg(target_os = "espidf")]
+use esp_idf_sys::EspError;
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(target_os = "espidf")]
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+//utc, there's no timezone setting yet
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WallClock {
+    pub year: i32,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+impl WallClock {
+    fn from_unix(seconds: u64) -> Self {
+        let days = (seconds / 86_400) as i64;
+        let time = seconds % 86_400;
+
+        //days since 1970-01-01 to a civil date, Howard Hinnant's algorithm
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u8;
+        let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u8;
+        let year = (yoe + era * 400 + if month <= 2 { 1 } else { 0 }) as i32;
+
+        Self {
+            year,
+            month,
+            day,
+            hour: (time / 3600) as u8,
+            minute: ((time / 60) % 60) as u8,
+            second: (time % 60) as u8,
+        }
+    }
+}
+
+//one shared instance (behind an Arc) so every screen agrees on when the device came up
+pub struct SystemClock {
+    boot: Instant,
+    #[cfg(target_os = "espidf")]
+    sntp: Mutex<Option<EspSntp<'static>>>,
+    synced: AtomicBool,
+}
+
+impl SystemClock {
+    //create it first thing in main, uptime counts from here
+    pub fn new() -> Self {
+        Self {
+            boot: Instant::now(),
+            #[cfg(target_os = "espidf")]
+            sntp: Mutex::new(None),
+            synced: AtomicBool::new(false),
+        }
+    }
+
+    pub fn uptime(&self) -> Duration {
+        self.boot.elapsed()
+    }
+
+    //cheap timestamp for logging and ordering, wraps after ~584 million years
+    pub fn now_millis(&self) -> u64 {
+        self.boot.elapsed().as_millis() as u64
+    }
+
+    pub fn format_uptime(&self) -> String {
+        let seconds = self.uptime().as_secs();
+        format!("{:02}:{:02}:{:02}", seconds / 3600, (seconds / 60) % 60, seconds % 60)
+    }
+
+    /// Starts SNTP in the background once the network is up. Returns straight away,
+    /// poll `update()` to find out when the time arrives. Calling it again is a no-op.
+    #[cfg(target_os = "espidf")]
+    pub fn sync_time(&self) -> Result<(), EspError> {
+        let mut sntp = self.sntp.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if sntp.is_none() {
+            //the sntp task keeps retrying on its own, so a wifi drop mid-sync just delays it
+            *sntp = Some(EspSntp::new_default()?);
+            log::info!("SNTP started");
+        }
+
+        Ok(())
+    }
+
+    //non-blocking, pushes TimeSynced the first time sntp reports a completed sync
+    pub fn update(&self, event_queue: &EventQueue) {
+        if self.is_synced() {
+            return;
+        }
+
+        #[cfg(target_os = "espidf")]
+        let completed = match &*self.sntp.lock().unwrap_or_else(PoisonError::into_inner) {
+            Some(sntp) => sntp.get_sync_status() == SyncStatus::Completed,
+            None => false,
+        };
+        //nothing ever syncs on the host
+        #[cfg(not(target_os = "espidf"))]
+        let completed = false;
+
+        if completed {
+            self.synced.store(true, Ordering::Release);
+            log::info!("Time synchronised");
+            event_queue.push(Event::TimeSynced);
+        }
+    }
+
+    pub fn is_synced(&self) -> bool {
+        self.synced.load(Ordering::Acquire)
+    }
+
+    //None until sntp has set the system time, before that it would read as 1970
+    pub fn wall_clock(&self) -> Option<WallClock> {
+        if !self.is_synced() {
+            return None;
+        }
+
+        let seconds = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs();
+        Some(WallClock::from_unix(seconds))
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,117 @@
+use crate::system::events::Event;
+use std::io::Write;
+use std::time::Instant;
+
+type EventFilter = Box<dyn Fn(&Event) -> bool + Send + Sync>;
+
+/// Mirrors every event pushed onto the queue to the serial console as one JSON object per
+/// line, for following along from a bench pc. Only built with the `event-log` feature.
+///
+/// ```text
+/// {"ms":5120,"event":"ButtonPressed","args":[25]}
+/// {"ms":5121,"event":"LaunchApp","args":["menu"]}
+/// ```
+pub struct EventLogger {
+    started: Instant,
+    filter: Option<EventFilter>,
+}
+
+impl EventLogger {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            filter: None,
+        }
+    }
+
+    //only events the filter passes get written, e.g. to keep SystemTick from flooding the console
+    pub fn with_filter<F>(mut self, filter: F) -> Self
+    where
+        F: Fn(&Event) -> bool + Send + Sync + 'static,
+    {
+        self.filter = Some(Box::new(filter));
+        self
+    }
+
+    pub fn log(&self, event: &Event) {
+        if self.filter.as_ref().is_some_and(|filter| !filter(event)) {
+            return;
+        }
+
+        let line = format!(r#"{{"ms":{},{}}}"#, self.started.elapsed().as_millis(), event_fields(event));
+
+        //a console that's gone away isn't worth failing a push over
+        let _ = writeln!(std::io::stdout().lock(), "{}", line);
+    }
+}
+
+impl Default for EventLogger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The event as a standalone JSON object, without the timestamp.
+pub fn to_json(event: &Event) -> String {
+    format!("{{{}}}", event_fields(event))
+}
+
+fn event_fields(event: &Event) -> String {
+    let (name, args): (&str, Vec<String>) = match event {
+        Event::ButtonPressed(pin) => ("ButtonPressed", vec![pin.to_string()]),
+        Event::ButtonReleased(pin) => ("ButtonReleased", vec![pin.to_string()]),
+        Event::ButtonLongPressed(pin) => ("ButtonLongPressed", vec![pin.to_string()]),
+        Event::ButtonClicked(pin) => ("ButtonClicked", vec![pin.to_string()]),
+        Event::ButtonDoubleClicked(pin) => ("ButtonDoubleClicked", vec![pin.to_string()]),
+        Event::ButtonRepeat(pin) => ("ButtonRepeat", vec![pin.to_string()]),
+        Event::EncoderRotated(id, direction) => ("EncoderRotated", vec![id.to_string(), direction.to_string()]),
+        Event::Select => ("Select", vec![]),
+        Event::Up => ("Up", vec![]),
+        Event::Down => ("Down", vec![]),
+        Event::Back => ("Back", vec![]),
+        Event::Timer(id) => ("Timer", vec![id.to_string()]),
+        Event::SystemTick => ("SystemTick", vec![]),
+        Event::LaunchApp(id) => ("LaunchApp", vec![json_string(id)]),
+        Event::AppLaunched(id) => ("AppLaunched", vec![json_string(id)]),
+        Event::AppClosed(id) => ("AppClosed", vec![json_string(id)]),
+        Event::NavigateTo(index) => ("NavigateTo", vec![index.to_string()]),
+        Event::NavigateBack => ("NavigateBack", vec![]),
+        Event::MenuSelected(index) => ("MenuSelected", vec![index.to_string()]),
+        Event::NetworkUp => ("NetworkUp", vec![]),
+        Event::NetworkDown => ("NetworkDown", vec![]),
+        Event::NetworkSignal(rssi) => ("NetworkSignal", vec![rssi.to_string()]),
+        Event::TimeSynced => ("TimeSynced", vec![]),
+        Event::BootComplete => ("BootComplete", vec![]),
+        Event::WakeUp => ("WakeUp", vec![]),
+        Event::BatteryLow(percent) => ("BatteryLow", vec![percent.to_string()]),
+        Event::BatteryLevel(percent) => ("BatteryLevel", vec![percent.to_string()]),
+        Event::Redraw => ("Redraw", vec![]),
+        Event::Custom(payload) => ("Custom", vec![json_string(payload)]),
+    };
+
+    if args.is_empty() {
+        format!(r#""event":"{}""#, name)
+    } else {
+        format!(r#""event":"{}","args":[{}]"#, name, args.join(","))
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+
+    out.push('"');
+    out
+}
@@ -1,61 +1,300 @@
-use esp_idf_hal::gpio::{Pin, PinDriver};
+use crate::drivers::input::ButtonState;
+use crate::system::time::Clock;
+#[cfg(feature = "event-log")]
+use crate::system::event_log::EventLogger;
+#[cfg(target_os = "espidf")]
+use esp_idf_hal::gpio::{AnyIOPin, IOPin, Input, Pin, PinDriver, Pull};
+#[cfg(target_os = "espidf")]
+use esp_idf_sys::EspError;
+use embedded_hal::digital::InputPin;
 use std::collections::VecDeque;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Event {
     ButtonPressed(u32),
     ButtonReleased(u32),
+    ButtonLongPressed(u32),
+    ButtonClicked(u32),
+    ButtonDoubleClicked(u32),
+    ButtonRepeat(u32),
+    EncoderRotated(u32, i8),
+    Select,
+    Up,
+    Down,
+    Back,
     Timer(u32),
     SystemTick,
+    LaunchApp(String),
     AppLaunched(String),
     AppClosed(String),
+    NavigateTo(usize),
+    NavigateBack,
+    MenuSelected(usize),
+    NetworkUp,
+    NetworkDown,
+    NetworkSignal(i8),
+    TimeSynced,
+    BootComplete,
+    WakeUp,
+    BatteryLow(u8),
+    BatteryLevel(u8),
+    Redraw,
     Custom(String),
 }
 
+impl Event {
+    //anything that means someone is at the device, WakeUp included since it's a held button
+    pub fn is_user_input(&self) -> bool {
+        matches!(
+            self,
+            Event::ButtonPressed(_)
+                | Event::ButtonReleased(_)
+                | Event::ButtonLongPressed(_)
+                | Event::ButtonClicked(_)
+                | Event::ButtonDoubleClicked(_)
+                | Event::ButtonRepeat(_)
+                | Event::EncoderRotated(_, _)
+                | Event::Select
+                | Event::Up
+                | Event::Down
+                | Event::Back
+                | Event::WakeUp
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EventPriority {
+    Low,
+    Normal,
+    High,
+}
+
 pub struct EventQueue {
-    queue: Arc<Mutex<VecDeque<Event>>>,
+    queue: Arc<Mutex<VecDeque<(EventPriority, Event)>>>,
+    history: Mutex<VecDeque<Event>>,
+    history_capacity: usize,
+    capacity: Option<usize>,
+    #[cfg(feature = "event-log")]
+    logger: Option<EventLogger>,
 }
 
 impl EventQueue {
     pub fn new() -> Self {
         Self {
             queue: Arc::new(Mutex::new(VecDeque::new())),
+            history: Mutex::new(VecDeque::new()),
+            history_capacity: 0,
+            capacity: None,
+            #[cfg(feature = "event-log")]
+            logger: None,
         }
     }
 
+    //every push is mirrored to the console from here on
+    #[cfg(feature = "event-log")]
+    pub fn with_logger(mut self, logger: EventLogger) -> Self {
+        self.logger = Some(logger);
+        self
+    }
+
+    //once full a push drops the oldest lowest-priority event, or itself if everything queued outranks it
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = Some(capacity.max(1));
+        self
+    }
+
+    //remembers the last capacity events pushed for debugging, allocated once here and never grown
+    pub fn with_history(mut self, capacity: usize) -> Self {
+        self.history = Mutex::new(VecDeque::with_capacity(capacity));
+        self.history_capacity = capacity;
+        self
+    }
+
+    //oldest first
+    pub fn recent_events(&self) -> Vec<Event> {
+        self.history.lock().unwrap().iter().cloned().collect()
+    }
+
+    fn record(&self, event: &Event) {
+        if self.history_capacity == 0 {
+            return;
+        }
+
+        let mut history = self.history.lock().unwrap();
+        if history.len() == self.history_capacity {
+            history.pop_front();
+        }
+        history.push_back(event.clone());
+    }
+
     pub fn push(&self, event: Event) {
+        self.push_with_priority(event, EventPriority::Normal);
+    }
+
+    pub fn push_with_priority(&self, event: Event, priority: EventPriority) {
+        let mut queue = self.queue.lock().unwrap();
+        self.insert_locked(&mut queue, event, priority);
+    }
+
+    pub fn push_coalesced(&self, event: Event) {
+        //only periodic events are safe to drop, input must always go through one by one
+        let coalescable = matches!(event, Event::SystemTick | Event::Timer(_) | Event::BatteryLevel(_));
+
+        //checked and inserted under the one lock, or two sources could both see no duplicate
         let mut queue = self.queue.lock().unwrap();
-        queue.push_back(event);
+        if coalescable && queue.iter().any(|(_, queued)| *queued == event) {
+            return;
+        }
+
+        self.insert_locked(&mut queue, event, EventPriority::Normal);
+    }
+
+    //the caller holds the queue lock, so whatever it checked beforehand still holds here
+    fn insert_locked(&self, queue: &mut VecDeque<(EventPriority, Event)>, event: Event, priority: EventPriority) {
+        //recorded as pushed rather than as handled, so the log still shows events stuck in the queue
+        self.record(&event);
+
+        #[cfg(feature = "event-log")]
+        if let Some(logger) = &self.logger {
+            logger.log(&event);
+        }
+
+        if self.capacity.is_some_and(|capacity| queue.len() >= capacity) {
+            //sorted high to low, so the oldest lowest-priority event is the first one sharing the tail's priority
+            let lowest = queue.back().map(|(queued, _)| *queued);
+
+            //everything queued outranks it, so it's the one that goes
+            if lowest.is_some_and(|lowest| priority < lowest) {
+                log::warn!("Event queue full, dropped {:?}", event);
+                return;
+            }
+
+            let index = queue.iter().position(|(queued, _)| Some(*queued) == lowest);
+            if let Some((_, dropped)) = index.and_then(|i| queue.remove(i)) {
+                log::warn!("Event queue full, dropped {:?}", dropped);
+            }
+        }
+
+        //queue stays sorted high to low, so insert behind the last event of equal or higher priority
+        let index = queue.iter()
+            .rposition(|(queued, _)| *queued >= priority)
+            .map_or(0, |i| i + 1);
+
+        queue.insert(index, (priority, event));
     }
 
     pub fn pop(&self) -> Option<Event> {
         let mut queue = self.queue.lock().unwrap();
-        queue.pop_front()
+        queue.pop_front().map(|(_, event)| event)
+    }
+
+    //events waiting to be handled, a backlog that keeps growing means the loop isn't keeping up
+    pub fn len(&self) -> usize {
+        self.queue.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.lock().unwrap().is_empty()
+    }
+
+    pub fn capacity(&self) -> Option<usize> {
+        self.capacity
+    }
+
+    //kept for the diagnostics screen, same as len()
+    pub fn queue_len(&self) -> usize {
+        self.len()
     }
 
-    pub fn get_queue_clone(&self) -> Arc<Mutex<VecDeque<Event>>> {
+    pub fn get_queue_clone(&self) -> Arc<Mutex<VecDeque<(EventPriority, Event)>>> {
         self.queue.clone()
     }
 }
 
+impl Default for EventQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ButtonMap {
+    pub select: u32,
+    pub scroll: u32,
+    pub back: Option<u32>,
+    pub encoder: Option<u32>,
+}
+
+impl ButtonMap {
+    //turns raw input into Select/Up/Down/Back, anything unmapped (releases included) is left alone
+    pub fn translate(&self, event: &Event) -> Option<Event> {
+        match event {
+            Event::ButtonPressed(pin) if *pin == self.select => Some(Event::Select),
+            Event::ButtonPressed(pin) | Event::ButtonRepeat(pin) if *pin == self.scroll => Some(Event::Down),
+            Event::ButtonPressed(pin) if Some(*pin) == self.back => Some(Event::Back),
+            Event::EncoderRotated(id, direction) if Some(*id) == self.encoder => {
+                if *direction > 0 {
+                    Some(Event::Down)
+                } else {
+                    Some(Event::Up)
+                }
+            },
+            _ => None,
+        }
+    }
+}
+
+impl Default for ButtonMap {
+    //the stock board only has scroll and select wired up
+    fn default() -> Self {
+        Self {
+            select: 26,
+            scroll: 25,
+            back: None,
+            encoder: None,
+        }
+    }
+}
+
 pub trait EventHandler {
     fn handle_event(&mut self, event: &Event) -> bool;
 }
 
-pub struct ButtonEventSource<'a, T: Pin> {
-    pin: PinDriver<'a, T, esp_idf_hal::gpio::Input>,
+//any embedded-hal input works, on the board that's a PinDriver (see AnyButtonEventSource)
+pub struct ButtonEventSource<P: InputPin> {
+    pin: P,
     pin_number: u32,
     event_queue: Arc<EventQueue>,
     last_state: bool,
     debounce_time: Duration,
-    last_event: Instant,
+    raw_state: bool,
+    raw_since: Instant,
+    long_press_threshold: Option<Duration>,
+    press_started: Option<Instant>,
+    long_press_fired: bool,
+    multi_click_window: Option<Duration>,
+    pending_click: Option<Instant>,
+    repeat: Option<(Duration, Duration)>,
+    next_repeat: Option<Instant>,
+    repeat_interval: Duration,
+    repeat_fired: bool,
+    clock: Clock,
 }
 
-impl <'a, T: Pin> ButtonEventSource<'a, T> {
+/// A button source with the pin type erased, so sources for different gpios can share a `Vec`.
+#[cfg(target_os = "espidf")]
+pub type AnyButtonEventSource<'a> = ButtonEventSource<PinDriver<'a, AnyIOPin, Input>>;
+
+//each repeat comes this much sooner than the last, down to a quarter of the configured interval
+const REPEAT_ACCELERATION: f32 = 0.85;
+const REPEAT_MIN_DIVISOR: u32 = 4;
+
+impl<P: InputPin> ButtonEventSource<P> {
     pub fn new(
-        pin: PinDriver<'a, T, esp_idf_hal::gpio::Input>,
+        pin: P,
         pin_number: u32,
         event_queue: Arc<EventQueue>,
     ) -> Self {
@@ -65,67 +304,294 @@ impl <'a, T: Pin> ButtonEventSource<'a, T> {
             event_queue,
             last_state: true,
             debounce_time: Duration::from_millis(50),
-            last_event: Instant::now(),
+            raw_state: true,
+            raw_since: Instant::now(),
+            long_press_threshold: None,
+            press_started: None,
+            long_press_fired: false,
+            multi_click_window: None,
+            pending_click: None,
+            repeat: None,
+            next_repeat: None,
+            repeat_interval: Duration::from_secs(0),
+            repeat_fired: false,
+            clock: Box::new(Instant::now),
         }
     }
 
+    //where poll reads the time from, so debounce and long-press timing can be stepped by hand
+    pub fn with_clock<F>(mut self, clock: F) -> Self
+    where
+        F: Fn() -> Instant + Send + 'static,
+    {
+        self.raw_since = clock();
+        self.clock = Box::new(clock);
+        self
+    }
+
+    pub fn with_debounce(mut self, debounce: Duration) -> Self {
+        self.debounce_time = debounce;
+        self
+    }
+
+    pub fn set_debounce(&mut self, debounce: Duration) {
+        self.debounce_time = debounce;
+    }
+
+    pub fn with_long_press(mut self, threshold: Duration) -> Self {
+        self.long_press_threshold = Some(threshold);
+        self
+    }
+
+    //adds ButtonClicked/ButtonDoubleClicked, a single click waits out the window so keep it short (~250ms)
+    pub fn with_multi_click(mut self, window: Duration) -> Self {
+        self.multi_click_window = Some(window);
+        self
+    }
+
+    //ButtonRepeat while held, first after delay then every interval, speeding up the longer it's held
+    pub fn with_repeat(mut self, delay: Duration, interval: Duration) -> Self {
+        self.repeat = Some((delay, interval));
+        self
+    }
+
+    pub fn set_repeat(&mut self, delay: Duration, interval: Duration) {
+        self.repeat = Some((delay, interval));
+    }
+
+    pub fn pin_number(&self) -> u32 {
+        self.pin_number
+    }
+
+    //the debounced state, so it agrees with the last Pressed/Released event pushed
+    pub fn state(&self) -> ButtonState {
+        if self.last_state {
+            ButtonState::Released
+        } else {
+            ButtonState::Pressed
+        }
+    }
 
     pub fn poll(&mut self) {
-        let current_state = self.pin.is_high();
-        let now = Instant::now();
+        //a failed read counts as no change rather than a press
+        let current_state = self.pin.is_high().unwrap_or(self.raw_state);
+        let now = (self.clock)();
+
+        //a reading only counts once it has held steady for the whole debounce window,
+        //so a single noisy sample after a long idle can't register as a press
+        if current_state != self.raw_state {
+            self.raw_state = current_state;
+            self.raw_since = now;
+        }
+
+        if now.duration_since(self.raw_since) >= self.debounce_time && current_state != self.last_state {
+
+            if current_state {
+                self.event_queue.push(Event::ButtonReleased(self.pin_number));
+                self.press_started = None;
+                self.next_repeat = None;
 
-        if now.duration_since(self.last_event) >= self.debounce_time {
-            if current_state != self.last_state {
+                //a long press or a held repeat is its own gesture, not a click
+                if self.multi_click_window.is_some() && !self.long_press_fired && !self.repeat_fired {
+                    self.register_click(now);
+                }
+            }else{
+                self.event_queue.push(Event::ButtonPressed(self.pin_number));
+                self.press_started = Some(now);
+                self.long_press_fired = false;
+                self.repeat_fired = false;
 
-                if current_state {
-                    self.event_queue.push(Event::ButtonReleased(self.pin_number));
-                }else{
-                    self.event_queue.push(Event::ButtonPressed(self.pin_number));
+                if let Some((delay, interval)) = self.repeat {
+                    self.next_repeat = Some(now + delay);
+                    self.repeat_interval = interval;
                 }
+            }
+
+            self.last_state = current_state;
+        }
+
+        if let (Some(threshold), Some(started)) = (self.long_press_threshold, self.press_started) {
+            if !self.long_press_fired && now.duration_since(started) >= threshold {
+                self.event_queue.push(Event::ButtonLongPressed(self.pin_number));
+                self.long_press_fired = true;
+            }
+        }
 
-                self.last_state = current_state;
-                self.last_event = now;
+        if let (Some((_, interval)), Some(due)) = (self.repeat, self.next_repeat) {
+            if now >= due {
+                self.event_queue.push(Event::ButtonRepeat(self.pin_number));
+                self.repeat_fired = true;
 
+                //schedule from now rather than the deadline so a slow poll loop doesn't burst repeats
+                self.next_repeat = Some(now + self.repeat_interval);
+                self.repeat_interval = self.repeat_interval.mul_f32(REPEAT_ACCELERATION).max(interval / REPEAT_MIN_DIVISOR);
+            }
+        }
+
+        if let (Some(window), Some(clicked_at)) = (self.multi_click_window, self.pending_click) {
+            if now.duration_since(clicked_at) > window {
+                self.event_queue.push(Event::ButtonClicked(self.pin_number));
+                self.pending_click = None;
             }
         }
     }
+
+    fn register_click(&mut self, now: Instant) {
+        match (self.multi_click_window, self.pending_click) {
+            (Some(window), Some(clicked_at)) if now.duration_since(clicked_at) <= window => {
+                self.event_queue.push(Event::ButtonDoubleClicked(self.pin_number));
+                self.pending_click = None;
+            },
+            _ => {
+                self.pending_click = Some(now);
+            },
+        }
+    }
 }
 
-pub struct TimerEventSource {
-    timer_id: u32,
+#[cfg(target_os = "espidf")]
+impl<'a> ButtonEventSource<PinDriver<'a, AnyIOPin, Input>> {
+    //the usual wiring, an active-low button with the internal pull-up, reported under its gpio number
+    pub fn from_pin(pin: impl IOPin + 'a, event_queue: Arc<EventQueue>) -> Result<Self, EspError> {
+        let pin = pin.downgrade();
+        let pin_number = pin.pin() as u32;
+
+        let mut driver = PinDriver::input(pin)?;
+        driver.set_pull(Pull::Up)?;
+
+        Ok(Self::new(driver, pin_number, event_queue))
+    }
+}
+
+//indexed by (previous AB state << 2) | current AB state, invalid jumps count as 0
+#[cfg(target_os = "espidf")]
+const QUADRATURE_TABLE: [i8; 16] = [0, -1, 1, 0, 1, 0, 0, -1, -1, 0, 0, 1, 0, 1, -1, 0];
+#[cfg(target_os = "espidf")]
+const STEPS_PER_DETENT: i8 = 4;
+
+#[cfg(target_os = "espidf")]
+pub struct EncoderEventSource<'a, A: Pin, B: Pin> {
+    pin_a: PinDriver<'a, A, esp_idf_hal::gpio::Input>,
+    pin_b: PinDriver<'a, B, esp_idf_hal::gpio::Input>,
+    encoder_id: u32,
     event_queue: Arc<EventQueue>,
-    interval: Duration,
-    last_triggered: Instant,
+    last_ab: u8,
+    steps: i8,
 }
 
-impl TimerEventSource {
+#[cfg(target_os = "espidf")]
+impl<'a, A: Pin, B: Pin> EncoderEventSource<'a, A, B> {
     pub fn new(
-        timer_id: u32,
-        interval: Duration,
+        pin_a: PinDriver<'a, A, esp_idf_hal::gpio::Input>,
+        pin_b: PinDriver<'a, B, esp_idf_hal::gpio::Input>,
+        encoder_id: u32,
         event_queue: Arc<EventQueue>,
     ) -> Self {
+        let last_ab = ((pin_a.is_high() as u8) << 1) | pin_b.is_high() as u8;
+
         Self {
-            timer_id,
+            pin_a,
+            pin_b,
+            encoder_id,
             event_queue,
-            interval, 
-            last_triggered: Instant::now(),
+            last_ab,
+            steps: 0,
         }
     }
 
     pub fn poll(&mut self) {
-        let now = Instant::now();
+        let ab = ((self.pin_a.is_high() as u8) << 1) | self.pin_b.is_high() as u8;
 
-        if now.duration_since(self.last_triggered) >= self.interval {
-            self.event_queue.push(Event::Timer(self.timer_id));
-            self.last_triggered = now;
+        if ab == self.last_ab {
+            return;
+        }
+
+        //bouncing on one channel steps back and forth and cancels itself out
+        self.steps += QUADRATURE_TABLE[((self.last_ab << 2) | ab) as usize];
+        self.last_ab = ab;
+
+        if self.steps >= STEPS_PER_DETENT {
+            self.event_queue.push(Event::EncoderRotated(self.encoder_id, 1));
+            self.steps = 0;
+        } else if self.steps <= -STEPS_PER_DETENT {
+            self.event_queue.push(Event::EncoderRotated(self.encoder_id, -1));
+            self.steps = 0;
         }
     }
 }
 
-pub struct SystemTickSource {
+//the cadence TimerEventSource and SystemTickSource share, they only differ in the event they push
+struct PeriodicSource {
+    event: Event,
     event_queue: Arc<EventQueue>,
-    interval: Duration,
+    interval: Arc<Mutex<Duration>>,
     last_triggered: Instant,
+    clock: Clock,
+}
+
+impl PeriodicSource {
+    fn new(event: Event, interval: Duration, event_queue: Arc<EventQueue>) -> Self {
+        Self {
+            event,
+            event_queue,
+            interval: Arc::new(Mutex::new(interval)),
+            last_triggered: Instant::now(),
+            clock: Box::new(Instant::now),
+        }
+    }
+
+    //the first event is timed from when the clock is swapped in
+    fn set_clock(&mut self, clock: Clock) {
+        self.last_triggered = clock();
+        self.clock = clock;
+    }
+
+    fn interval(&self) -> Duration {
+        *self.interval.lock().unwrap()
+    }
+
+    fn poll(&mut self) {
+        let now = (self.clock)();
+
+        if now.duration_since(self.last_triggered) >= self.interval() {
+            self.event_queue.push_coalesced(self.event.clone());
+            self.last_triggered = now;
+        }
+    }
+}
+
+pub struct TimerEventSource {
+    source: PeriodicSource,
+}
+
+impl TimerEventSource {
+    pub fn new(
+        timer_id: u32,
+        interval: Duration,
+        event_queue: Arc<EventQueue>,
+    ) -> Self {
+        Self {
+            source: PeriodicSource::new(Event::Timer(timer_id), interval, event_queue),
+        }
+    }
+
+    //where poll reads the time from, so the cadence can be driven by hand
+    pub fn with_clock<F>(mut self, clock: F) -> Self
+    where
+        F: Fn() -> Instant + Send + 'static,
+    {
+        self.source.set_clock(Box::new(clock));
+        self
+    }
+
+    pub fn poll(&mut self) {
+        self.source.poll();
+    }
+}
+
+pub struct SystemTickSource {
+    //the interval is shared so whatever changes the tick rate (the settings screen) doesn't need the source itself
+    source: PeriodicSource,
 }
 
 impl SystemTickSource {
@@ -134,18 +600,234 @@ impl SystemTickSource {
         event_queue: Arc<EventQueue>,
     ) -> Self {
         Self {
-            event_queue,
-            interval,
-            last_triggered: Instant::now(),
+            source: PeriodicSource::new(Event::SystemTick, interval, event_queue),
         }
     }
 
+    //where poll reads the time from, so the cadence can be driven by hand
+    pub fn with_clock<F>(mut self, clock: F) -> Self
+    where
+        F: Fn() -> Instant + Send + 'static,
+    {
+        self.source.set_clock(Box::new(clock));
+        self
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.source.interval()
+    }
+
+    //takes effect from the next poll, measured from the last tick
+    pub fn set_interval(&self, interval: Duration) {
+        *self.source.interval.lock().unwrap() = interval;
+    }
+
+    pub fn interval_handle(&self) -> Arc<Mutex<Duration>> {
+        self.source.interval.clone()
+    }
+
     pub fn poll(&mut self) {
-        let now = Instant::now();
+        self.source.poll();
+    }
+}
 
-        if now.duration_since(self.last_triggered) >= self.interval {
-            self.event_queue.push(Event::SystemTick);
-            self.last_triggered = now;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::Infallible;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    //an active-low button on a pulled-up pin: idles high, the test pulls it low to press
+    #[derive(Clone)]
+    struct FakePin(Arc<AtomicBool>);
+
+    impl FakePin {
+        fn pulled_up() -> Self {
+            Self(Arc::new(AtomicBool::new(true)))
         }
+
+        fn set_high(&self, high: bool) {
+            self.0.store(high, Ordering::Relaxed);
+        }
+    }
+
+    impl embedded_hal::digital::ErrorType for FakePin {
+        type Error = Infallible;
+    }
+
+    impl InputPin for FakePin {
+        fn is_high(&mut self) -> Result<bool, Infallible> {
+            Ok(self.0.load(Ordering::Relaxed))
+        }
+
+        fn is_low(&mut self) -> Result<bool, Infallible> {
+            Ok(!self.0.load(Ordering::Relaxed))
+        }
+    }
+
+    fn drain(event_queue: &EventQueue) -> Vec<Event> {
+        std::iter::from_fn(|| event_queue.pop()).collect()
+    }
+
+    //from_pin needs a real gpio, so these build sources the way it does: idle high, reported under the pin number
+    fn button(pin: &FakePin, pin_number: u32, event_queue: &Arc<EventQueue>) -> ButtonEventSource<FakePin> {
+        ButtonEventSource::new(pin.clone(), pin_number, event_queue.clone()).with_debounce(Duration::ZERO)
+    }
+
+    #[test]
+    fn idle_pull_up_reads_as_released() {
+        let event_queue = Arc::new(EventQueue::new());
+        let pin = FakePin::pulled_up();
+        let mut source = button(&pin, 25, &event_queue);
+
+        source.poll();
+
+        assert_eq!(source.pin_number(), 25);
+        assert_eq!(source.state(), ButtonState::Released);
+        assert!(event_queue.is_empty());
+    }
+
+    #[test]
+    fn sources_in_one_vec_report_under_their_own_pins() {
+        let event_queue = Arc::new(EventQueue::new());
+        let scroll = FakePin::pulled_up();
+        let select = FakePin::pulled_up();
+        let mut sources: Vec<_> = [(&scroll, 25), (&select, 26)]
+            .into_iter()
+            .map(|(pin, pin_number)| button(pin, pin_number, &event_queue))
+            .collect();
+
+        select.set_high(false);
+        sources.iter_mut().for_each(|source| source.poll());
+        assert_eq!(drain(&event_queue), vec![Event::ButtonPressed(26)]);
+
+        scroll.set_high(false);
+        select.set_high(true);
+        sources.iter_mut().for_each(|source| source.poll());
+        assert_eq!(drain(&event_queue), vec![Event::ButtonPressed(25), Event::ButtonReleased(26)]);
+
+        assert_eq!(sources[0].state(), ButtonState::Pressed);
+        assert_eq!(sources[1].state(), ButtonState::Released);
+    }
+
+    //polls the pin at each (ms since start, level), the source keeps its default 50ms debounce
+    fn poll_trace(trace: &[(u64, bool)]) -> Vec<Event> {
+        let event_queue = Arc::new(EventQueue::new());
+        let pin = FakePin::pulled_up();
+        let (now, clock) = manual_clock();
+        let start = *now.lock().unwrap();
+        let mut source = ButtonEventSource::new(pin.clone(), 25, event_queue.clone()).with_clock(clock);
+
+        for &(ms, high) in trace {
+            *now.lock().unwrap() = start + Duration::from_millis(ms);
+            pin.set_high(high);
+            source.poll();
+        }
+
+        drain(&event_queue)
+    }
+
+    #[test]
+    fn chatter_inside_the_debounce_window_gives_one_press() {
+        let events = poll_trace(&[
+            (0, false),
+            (10, true),
+            (20, false),
+            (30, true),
+            (35, false),
+            (70, false),
+            //50ms after the last bounce
+            (85, false),
+            (200, false),
+        ]);
+
+        assert_eq!(events, vec![Event::ButtonPressed(25)]);
+    }
+
+    #[test]
+    fn glitch_shorter_than_the_debounce_window_is_ignored() {
+        let events = poll_trace(&[(0, false), (40, true), (100, true), (500, true)]);
+
+        assert!(events.is_empty(), "{:?}", events);
+    }
+
+    fn manual_clock() -> (Arc<Mutex<Instant>>, impl Fn() -> Instant + Send + 'static) {
+        let now = Arc::new(Mutex::new(Instant::now()));
+        let reader = now.clone();
+        (now, move || *reader.lock().unwrap())
+    }
+
+    fn advance(now: &Mutex<Instant>, by: Duration) {
+        *now.lock().unwrap() += by;
+    }
+
+    #[test]
+    fn tick_fires_once_per_interval() {
+        let event_queue = Arc::new(EventQueue::new());
+        let (now, clock) = manual_clock();
+        let mut ticks = SystemTickSource::new(Duration::from_millis(100), event_queue.clone()).with_clock(clock);
+
+        ticks.poll();
+        assert!(event_queue.is_empty());
+
+        advance(&now, Duration::from_millis(99));
+        ticks.poll();
+        assert!(event_queue.is_empty());
+
+        advance(&now, Duration::from_millis(1));
+        ticks.poll();
+        assert_eq!(drain(&event_queue), vec![Event::SystemTick]);
+
+        //the next one is measured from that tick, not from the first poll
+        advance(&now, Duration::from_millis(50));
+        ticks.poll();
+        assert!(event_queue.is_empty());
+
+        advance(&now, Duration::from_millis(50));
+        ticks.poll();
+        assert_eq!(drain(&event_queue), vec![Event::SystemTick]);
+    }
+
+    #[test]
+    fn timer_fires_its_id_once_per_interval() {
+        let event_queue = Arc::new(EventQueue::new());
+        let (now, clock) = manual_clock();
+        let mut timer = TimerEventSource::new(7, Duration::from_millis(10), event_queue.clone()).with_clock(clock);
+
+        let mut fired = 0;
+        for _ in 0..50 {
+            advance(&now, Duration::from_millis(1));
+            timer.poll();
+
+            for event in drain(&event_queue) {
+                assert_eq!(event, Event::Timer(7));
+                fired += 1;
+            }
+        }
+
+        assert_eq!(fired, 5);
+    }
+
+    #[test]
+    fn set_interval_applies_from_the_last_tick() {
+        let event_queue = Arc::new(EventQueue::new());
+        let (now, clock) = manual_clock();
+        let mut ticks = SystemTickSource::new(Duration::from_millis(100), event_queue.clone()).with_clock(clock);
+
+        advance(&now, Duration::from_millis(100));
+        ticks.poll();
+        drain(&event_queue);
+
+        //the way the settings screen changes it, through the shared handle
+        *ticks.interval_handle().lock().unwrap() = Duration::from_millis(500);
+        assert_eq!(ticks.interval(), Duration::from_millis(500));
+
+        advance(&now, Duration::from_millis(400));
+        ticks.poll();
+        assert!(event_queue.is_empty());
+
+        advance(&now, Duration::from_millis(100));
+        ticks.poll();
+        assert_eq!(drain(&event_queue), vec![Event::SystemTick]);
     }
 }
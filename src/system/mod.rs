@@ -1,2 +1,12 @@
 pub mod events;
 pub mod scheduler;
+#[cfg(target_os = "espidf")]
+pub mod settings;
+#[cfg(target_os = "espidf")]
+pub mod wifi;
+pub mod power;
+pub mod notifications;
+pub mod time;
+pub mod clock;
+#[cfg(feature = "event-log")]
+pub mod event_log;
@@ -0,0 +1,76 @@
+use crate::drivers::display::{Canvas, DisplayBackend, DisplayError, TextSize};
+use crate::system::events::{Event, EventPriority, EventQueue};
+use crate::system::scheduler::Scheduler;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+const BANNER_HEIGHT: u32 = 12;
+
+pub struct NotificationManager {
+    display: Arc<dyn DisplayBackend>,
+    event_queue: Arc<EventQueue>,
+    pending: VecDeque<(String, Duration)>,
+    current: Option<String>,
+    expired: Arc<AtomicBool>,
+}
+
+impl NotificationManager {
+    pub fn new(display: Arc<dyn DisplayBackend>, event_queue: Arc<EventQueue>) -> Self {
+        Self {
+            display,
+            event_queue,
+            pending: VecDeque::new(),
+            current: None,
+            expired: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn notify(&mut self, text: &str, duration: Duration) {
+        self.pending.push_back((text.to_string(), duration));
+    }
+
+    pub fn is_showing(&self) -> bool {
+        self.current.is_some()
+    }
+
+    //scheduler callbacks can't touch the display or reschedule, so they only flip a flag we pick up here
+    pub fn update(&mut self, scheduler: &mut Scheduler) {
+        if self.current.is_some() && self.expired.swap(false, Ordering::SeqCst) {
+            self.current = None;
+
+            //the banner drew straight over the screen's pixels, have the screen paint them back
+            self.event_queue.push_with_priority(Event::Redraw, EventPriority::High);
+        }
+
+        if self.current.is_none() {
+            if let Some((text, duration)) = self.pending.pop_front() {
+                self.current = Some(text);
+
+                if let Err(e) = self.draw_banner() {
+                    log::error!("Failed to draw notification: {}", e);
+                }
+
+                let expired = self.expired.clone();
+                scheduler.schedule_once(duration, move || {
+                    expired.store(true, Ordering::SeqCst);
+                });
+            }
+        }
+    }
+
+    pub fn draw_banner(&self) -> Result<(), DisplayError> {
+        let text = match &self.current {
+            Some(text) => text,
+            None => return Ok(()),
+        };
+
+        let width = self.display.width();
+
+        self.display.clear_region(0, 0, width, BANNER_HEIGHT)?;
+        self.display.draw_rectangle(0, 0, width, BANNER_HEIGHT, false)?;
+        self.display.draw_text(text, 3, 2, TextSize::Small)?;
+        self.display.flush_dirty()
+    }
+}
@@ -0,0 +1,146 @@
+use crate::drivers::display::DisplayBackend;
+use crate::system::events::Event;
+#[cfg(target_os = "espidf")]
+use crate::system::events::{EventPriority, EventQueue};
+#[cfg(target_os = "espidf")]
+use esp_idf_sys::EspError;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+#[cfg(target_os = "espidf")]
+pub struct PowerManager {
+    display: Arc<dyn DisplayBackend>,
+    event_queue: Arc<EventQueue>,
+    idle_timeout: Duration,
+    last_activity: Instant,
+    wake_pins: Vec<i32>,
+}
+
+#[cfg(target_os = "espidf")]
+impl PowerManager {
+    pub fn new(display: Arc<dyn DisplayBackend>, event_queue: Arc<EventQueue>, idle_timeout: Duration) -> Self {
+        Self {
+            display,
+            event_queue,
+            idle_timeout,
+            last_activity: Instant::now(),
+            wake_pins: Vec::new(),
+        }
+    }
+
+    pub fn set_idle_timeout(&mut self, idle_timeout: Duration) {
+        self.idle_timeout = idle_timeout;
+    }
+
+    //buttons are wired active-low with pull-ups, so wake on a low level
+    pub fn register_wake_pin(&mut self, gpio: i32) -> Result<(), EspError> {
+        esp_idf_sys::esp!(unsafe {
+            esp_idf_sys::gpio_wakeup_enable(gpio, esp_idf_sys::gpio_int_type_t_GPIO_INTR_LOW_LEVEL)
+        })?;
+        esp_idf_sys::esp!(unsafe { esp_idf_sys::esp_sleep_enable_gpio_wakeup() })?;
+
+        self.wake_pins.push(gpio);
+        Ok(())
+    }
+
+    pub fn on_activity(&mut self) {
+        self.last_activity = Instant::now();
+    }
+
+    pub fn update(&mut self) {
+        //any held wake button counts as activity, so we never sleep out from under a press
+        let pressed = self.wake_pins.iter()
+            .any(|pin| unsafe { esp_idf_sys::gpio_get_level(*pin) } == 0);
+
+        if pressed {
+            self.on_activity();
+            return;
+        }
+
+        if self.wake_pins.is_empty() || self.last_activity.elapsed() < self.idle_timeout {
+            return;
+        }
+
+        self.sleep();
+    }
+
+    fn sleep(&mut self) {
+        log::info!("Idle for {:?}, entering light sleep", self.idle_timeout);
+
+        if let Err(e) = self.display.set_display_on(false) {
+            log::error!("Failed to turn display off before sleep: {}", e);
+        }
+
+        if let Err(e) = esp_idf_sys::esp!(unsafe { esp_idf_sys::esp_light_sleep_start() }) {
+            log::error!("Light sleep failed: {}", e);
+        }
+
+        if let Err(e) = self.display.set_display_on(true) {
+            log::error!("Failed to turn display back on: {}", e);
+        }
+
+        log::info!("Woke from light sleep");
+        self.on_activity();
+        self.event_queue.push_with_priority(Event::WakeUp, EventPriority::High);
+    }
+}
+
+pub struct IdleDimmer {
+    display: Arc<dyn DisplayBackend>,
+    timeout: Duration,
+    dim_level: u8,
+    last_activity: Instant,
+    restore_level: Option<u8>,
+}
+
+impl IdleDimmer {
+    pub fn new(display: Arc<dyn DisplayBackend>, timeout: Duration, dim_level: u8) -> Self {
+        Self {
+            display,
+            timeout,
+            dim_level,
+            last_activity: Instant::now(),
+            restore_level: None,
+        }
+    }
+
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    pub fn set_dim_level(&mut self, dim_level: u8) {
+        self.dim_level = dim_level;
+    }
+
+    pub fn is_dimmed(&self) -> bool {
+        self.restore_level.is_some()
+    }
+
+    pub fn on_event(&mut self, event: &Event) {
+        //periodic events would keep the screen lit forever, only user input counts
+        if !event.is_user_input() {
+            return;
+        }
+
+        self.last_activity = Instant::now();
+
+        if let Some(level) = self.restore_level.take() {
+            if let Err(e) = self.display.set_contrast(level) {
+                log::error!("Failed to restore contrast: {}", e);
+            }
+        }
+    }
+
+    pub fn update(&mut self) {
+        if self.is_dimmed() || self.last_activity.elapsed() < self.timeout {
+            return;
+        }
+
+        //remember whatever contrast the user had so settings changes survive a dim cycle
+        let current = self.display.contrast();
+        match self.display.set_contrast(self.dim_level.min(current)) {
+            Ok(_) => self.restore_level = Some(current),
+            Err(e) => log::error!("Failed to dim display: {}", e),
+        }
+    }
+}
@@ -1,8 +1,9 @@
-use std::collections::BinaryHeap;
+use std::collections::{BinaryHeap, HashMap};
 use std::cmp::{Ord, Ordering, PartialOrd};
 use std::time::{Duration, Instant};
 use std::sync::{Arc, Mutex};
 use crate::system::events::{Event, EventQueue};
+use crate::system::time::Clock;
 
 #[derive(Clone)]
 struct ScheduledTask {
@@ -36,19 +37,36 @@ impl PartialOrd for ScheduledTask {
 
 pub struct Scheduler {
     tasks: Arc<Mutex<BinaryHeap<ScheduledTask>>>,
+    paused: Arc<Mutex<HashMap<u32, ScheduledTask>>>,
     next_id: u32,
     event_queue: Arc<EventQueue>,
+    clock: Clock,
 }
 
 impl Scheduler {
     pub fn new(event_queue: Arc<EventQueue>) -> Self {
         Self {
             tasks: Arc::new(Mutex::new(BinaryHeap::new())),
+            paused: Arc::new(Mutex::new(HashMap::new())),
             next_id: 0,
             event_queue,
+            clock: Box::new(Instant::now),
         }
     }
 
+    //where deadlines are measured from, so tests can step time by hand
+    pub fn with_clock<F>(mut self, clock: F) -> Self
+    where
+        F: Fn() -> Instant + Send + 'static,
+    {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    fn now(&self) -> Instant {
+        (self.clock)()
+    }
+
     pub fn schedule_once<F>(&mut self, delay: Duration, callback: F) -> u32
     where
         F: Fn() + Send + Sync + 'static,
@@ -58,7 +76,7 @@ impl Scheduler {
 
         let task = ScheduledTask {
             id: task_id,
-            next_run: Instant::now() + delay,
+            next_run: self.now() + delay,
             interval: None,
             callback: Arc::new(callback),
         };
@@ -78,7 +96,7 @@ impl Scheduler {
 
         let task = ScheduledTask {
             id: task_id,
-            next_run: Instant::now() + delay,
+            next_run: self.now() + delay,
             interval: Some(interval),
             callback: Arc::new(callback),
         };
@@ -102,11 +120,77 @@ impl Scheduler {
 
         *tasks = new_heap;
 
-        old_len != tasks.len()
+        let was_paused = self.paused.lock().unwrap().remove(&id).is_some();
+
+        old_len != tasks.len() || was_paused
+    }
+
+    pub fn pause_task(&mut self, id: u32) -> bool {
+        let mut tasks = self.tasks.lock().unwrap();
+        let mut found = None;
+
+        let mut new_heap = BinaryHeap::new();
+        for task in tasks.drain() {
+            if task.id == id {
+                found = Some(task);
+            } else {
+                new_heap.push(task);
+            }
+        }
+
+        *tasks = new_heap;
+
+        match found {
+            Some(task) => {
+                self.paused.lock().unwrap().insert(id, task);
+                true
+            },
+            None => false,
+        }
+    }
+
+    pub fn resume_task(&mut self, id: u32) -> bool {
+        let task = match self.paused.lock().unwrap().remove(&id) {
+            Some(task) => task,
+            None => return false,
+        };
+
+        let now = self.now();
+        let next_run = match task.interval {
+            Some(interval) => now + interval,
+            //one-shot tasks keep their original deadline, firing straight away if it passed
+            None => task.next_run.max(now),
+        };
+
+        let mut tasks = self.tasks.lock().unwrap();
+        tasks.push(ScheduledTask {
+            next_run,
+            ..task
+        });
+
+        true
+    }
+
+    pub fn time_until_next_run(&self, id: u32) -> Option<Duration> {
+        let tasks = self.tasks.lock().unwrap();
+        let now = self.now();
+
+        tasks.iter()
+            .find(|task| task.id == id)
+            .map(|task| task.next_run.saturating_duration_since(now))
+    }
+
+    pub fn list_tasks(&self) -> Vec<u32> {
+        let tasks = self.tasks.lock().unwrap();
+
+        let mut pending: Vec<&ScheduledTask> = tasks.iter().collect();
+        pending.sort_by_key(|task| task.next_run);
+
+        pending.iter().map(|task| task.id).collect()
     }
 
     pub fn update(&mut self) {
-        let now = Instant::now();
+        let now = self.now();
         let mut tasks_to_reschedule = Vec::new();
 
         {
@@ -118,12 +202,19 @@ impl Scheduler {
 
                     (task.callback)();
 
-                    self.event_queue.push(Event::Timer(task.id));
+                    self.event_queue.push_coalesced(Event::Timer(task.id));
 
                     if let Some(interval) = task.interval {
+                        //step from the previous deadline so slow polling doesn't drift the period,
+                        //but don't try to catch up on runs we missed entirely
+                        let mut next_run = task.next_run + interval;
+                        if next_run <= now {
+                            next_run = now + interval;
+                        }
+
                         tasks_to_reschedule.push(ScheduledTask {
                             id: task.id,
-                            next_run: Instant::now() + interval,
+                            next_run,
                             interval: Some(interval),
                             callback: task.callback,
                         });
@@ -143,4 +234,71 @@ impl Scheduler {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn manual_clock() -> (Arc<Mutex<Instant>>, impl Fn() -> Instant + Send + 'static) {
+        let now = Arc::new(Mutex::new(Instant::now()));
+        let reader = now.clone();
+        (now, move || *reader.lock().unwrap())
+    }
+
+    fn advance(now: &Mutex<Instant>, by: Duration) {
+        *now.lock().unwrap() += by;
+    }
+
+    fn counting_task(scheduler: &mut Scheduler, interval: Duration) -> (u32, Arc<AtomicU32>) {
+        let runs = Arc::new(AtomicU32::new(0));
+        let counter = runs.clone();
+        let id = scheduler.schedule_recurring(interval, interval, move || {
+            counter.fetch_add(1, Ordering::Relaxed);
+        });
+        (id, runs)
+    }
 
+    #[test]
+    fn recurring_task_runs_once_per_interval() {
+        let event_queue = Arc::new(EventQueue::new());
+        let (now, clock) = manual_clock();
+        let mut scheduler = Scheduler::new(event_queue.clone()).with_clock(clock);
+        let (id, runs) = counting_task(&mut scheduler, Duration::from_millis(10));
+
+        for _ in 0..50 {
+            advance(&now, Duration::from_millis(1));
+            scheduler.update();
+        }
+
+        assert_eq!(runs.load(Ordering::Relaxed), 5);
+        assert_eq!(event_queue.pop(), Some(Event::Timer(id)));
+    }
+
+    #[test]
+    fn late_update_keeps_the_original_cadence() {
+        let (now, clock) = manual_clock();
+        let mut scheduler = Scheduler::new(Arc::new(EventQueue::new())).with_clock(clock);
+        let (id, runs) = counting_task(&mut scheduler, Duration::from_millis(10));
+
+        advance(&now, Duration::from_millis(13));
+        scheduler.update();
+
+        //stepped from the 10ms deadline, not from when update got round to it
+        assert_eq!(runs.load(Ordering::Relaxed), 1);
+        assert_eq!(scheduler.time_until_next_run(id), Some(Duration::from_millis(7)));
+    }
+
+    #[test]
+    fn missed_runs_are_skipped_not_replayed() {
+        let (now, clock) = manual_clock();
+        let mut scheduler = Scheduler::new(Arc::new(EventQueue::new())).with_clock(clock);
+        let (id, runs) = counting_task(&mut scheduler, Duration::from_millis(10));
+
+        advance(&now, Duration::from_millis(35));
+        scheduler.update();
+        scheduler.update();
+
+        assert_eq!(runs.load(Ordering::Relaxed), 1);
+        assert_eq!(scheduler.time_until_next_run(id), Some(Duration::from_millis(10)));
+    }
+}
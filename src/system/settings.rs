@@ -0,0 +1,93 @@
+use esp_idf_svc::nvs::{EspDefaultNvsPartition, EspNvs, NvsDefault};
+use esp_idf_sys::EspError;
+use std::error::Error;
+use std::fmt;
+
+//nvs keys are capped at 15 characters by esp-idf
+const MAX_KEY_LEN: usize = 15;
+
+#[derive(Debug)]
+pub enum SettingsError {
+    InvalidKey,
+    NvsError(EspError),
+}
+
+impl fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SettingsError::InvalidKey => write!(f, "Settings key must be 1-{} characters", MAX_KEY_LEN),
+            SettingsError::NvsError(e) => write!(f, "NVS error: {}", e),
+        }
+    }
+}
+
+impl Error for SettingsError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SettingsError::NvsError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<EspError> for SettingsError {
+    fn from(error: EspError) -> Self {
+        SettingsError::NvsError(error)
+    }
+}
+
+pub struct SettingsStore {
+    nvs: EspNvs<NvsDefault>,
+}
+
+impl SettingsStore {
+    pub fn new(partition: EspDefaultNvsPartition, namespace: &str) -> Result<Self, SettingsError> {
+        let nvs = EspNvs::new(partition, namespace, true)?;
+        log::info!("Settings store opened (namespace: {})", namespace);
+
+        Ok(Self { nvs })
+    }
+
+    pub fn get_u32(&self, key: &str, default: u32) -> Result<u32, SettingsError> {
+        Self::check_key(key)?;
+
+        //a missing key just means first boot, not an error
+        Ok(self.nvs.get_u32(key)?.unwrap_or(default))
+    }
+
+    pub fn set_u32(&mut self, key: &str, value: u32) -> Result<(), SettingsError> {
+        Self::check_key(key)?;
+        self.nvs.set_u32(key, value)?;
+        Ok(())
+    }
+
+    pub fn get_str(&self, key: &str, default: &str) -> Result<String, SettingsError> {
+        Self::check_key(key)?;
+
+        let len = match self.nvs.str_len(key)? {
+            Some(len) => len,
+            None => return Ok(default.to_string()),
+        };
+
+        let mut buf = vec![0u8; len + 1];
+        Ok(self.nvs.get_str(key, &mut buf)?.unwrap_or(default).to_string())
+    }
+
+    pub fn set_str(&mut self, key: &str, value: &str) -> Result<(), SettingsError> {
+        Self::check_key(key)?;
+        self.nvs.set_str(key, value)?;
+        Ok(())
+    }
+
+    pub fn commit(&mut self) -> Result<(), SettingsError> {
+        esp_idf_sys::esp!(unsafe { esp_idf_sys::nvs_commit(self.nvs.handle()) })?;
+        Ok(())
+    }
+
+    fn check_key(key: &str) -> Result<(), SettingsError> {
+        if key.is_empty() || key.len() > MAX_KEY_LEN {
+            return Err(SettingsError::InvalidKey);
+        }
+        Ok(())
+    }
+}
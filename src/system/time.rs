@@ -0,0 +1,93 @@
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_FPS: u32 = 30;
+//anything faster would round the frame down to zero microseconds
+pub const MAX_FPS: u32 = 1_000_000;
+
+//where time-driven code reads the current time from, Instant::now unless a test swaps it
+pub type Clock = Box<dyn Fn() -> Instant + Send>;
+
+pub struct FrameTimer {
+    frame_duration: Duration,
+    last_check: Instant,
+    accumulated: Duration,
+    dropped_frames: u32,
+}
+
+impl FrameTimer {
+    pub fn new(fps: u32) -> Self {
+        Self {
+            frame_duration: frame_duration_for(fps),
+            last_check: Instant::now(),
+            accumulated: Duration::ZERO,
+            dropped_frames: 0,
+        }
+    }
+
+    pub fn set_fps(&mut self, fps: u32) {
+        self.frame_duration = frame_duration_for(fps);
+        self.accumulated = Duration::ZERO;
+    }
+
+    pub fn frame_duration(&self) -> Duration {
+        self.frame_duration
+    }
+
+    pub fn should_render(&mut self) -> bool {
+        let now = Instant::now();
+        self.accumulated += now.duration_since(self.last_check);
+        self.last_check = now;
+
+        if self.accumulated < self.frame_duration {
+            return false;
+        }
+
+        //when we're behind only render once and skip the missed frames rather than bursting to catch up
+        let frames = (self.accumulated.as_micros() / self.frame_duration.as_micros()) as u32;
+        self.dropped_frames = self.dropped_frames.saturating_add(frames - 1);
+        self.accumulated -= self.frame_duration * frames;
+
+        true
+    }
+
+    pub fn dropped_frames(&self) -> u32 {
+        self.dropped_frames
+    }
+
+    pub fn reset(&mut self) {
+        self.last_check = Instant::now();
+        self.accumulated = Duration::ZERO;
+        self.dropped_frames = 0;
+    }
+}
+
+impl Default for FrameTimer {
+    fn default() -> Self {
+        Self::new(DEFAULT_FPS)
+    }
+}
+
+//never zero, callers divide by it or loop until it's used up
+pub fn frame_duration_for(fps: u32) -> Duration {
+    Duration::from_micros(1_000_000 / fps.clamp(1, MAX_FPS) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_duration_is_clamped_to_a_nonzero_range() {
+        assert_eq!(frame_duration_for(0), Duration::from_secs(1));
+        assert_eq!(frame_duration_for(30), Duration::from_micros(33_333));
+        assert_eq!(frame_duration_for(u32::MAX), Duration::from_micros(1));
+    }
+
+    #[test]
+    fn absurd_frame_rate_still_renders() {
+        let mut timer = FrameTimer::new(u32::MAX);
+        std::thread::sleep(Duration::from_millis(1));
+
+        assert!(timer.should_render());
+    }
+}
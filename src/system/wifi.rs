@@ -0,0 +1,124 @@
+use crate::system::events::{Event, EventQueue};
+use esp_idf_hal::modem::Modem;
+use esp_idf_svc::eventloop::EspSystemEventLoop;
+use esp_idf_svc::nvs::EspDefaultNvsPartition;
+use esp_idf_svc::wifi::{AuthMethod, BlockingWifi, ClientConfiguration, Configuration, EspWifi};
+use esp_idf_sys::EspError;
+use std::error::Error;
+use std::fmt;
+use std::net::Ipv4Addr;
+use std::sync::Arc;
+
+#[derive(Debug)]
+pub enum WifiError {
+    InvalidCredentials,
+    DriverError(EspError),
+    ConnectionFailed(EspError),
+}
+
+impl fmt::Display for WifiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WifiError::InvalidCredentials => write!(f, "SSID or password too long"),
+            WifiError::DriverError(e) => write!(f, "WiFi driver error: {}", e),
+            WifiError::ConnectionFailed(e) => write!(f, "WiFi connection failed: {}", e),
+        }
+    }
+}
+
+impl Error for WifiError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            WifiError::DriverError(e) => Some(e),
+            WifiError::ConnectionFailed(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<EspError> for WifiError {
+    fn from(error: EspError) -> Self {
+        WifiError::DriverError(error)
+    }
+}
+
+pub struct WifiManager {
+    wifi: BlockingWifi<EspWifi<'static>>,
+    event_queue: Arc<EventQueue>,
+}
+
+impl WifiManager {
+    pub fn new(
+        modem: Modem,
+        sysloop: EspSystemEventLoop,
+        nvs: Option<EspDefaultNvsPartition>,
+        event_queue: Arc<EventQueue>,
+    ) -> Result<Self, WifiError> {
+        let wifi = BlockingWifi::wrap(EspWifi::new(modem, sysloop.clone(), nvs)?, sysloop)?;
+
+        Ok(Self {
+            wifi,
+            event_queue,
+        })
+    }
+
+    pub fn connect(&mut self, ssid: &str, password: &str) -> Result<(), WifiError> {
+        let auth_method = if password.is_empty() { AuthMethod::None } else { AuthMethod::WPA2Personal };
+
+        let config = Configuration::Client(ClientConfiguration {
+            ssid: ssid.try_into().map_err(|_| WifiError::InvalidCredentials)?,
+            password: password.try_into().map_err(|_| WifiError::InvalidCredentials)?,
+            auth_method,
+            ..Default::default()
+        });
+
+        self.wifi.set_configuration(&config)?;
+
+        if !self.wifi.is_started()? {
+            self.wifi.start()?;
+        }
+
+        log::info!("Connecting to {}...", ssid);
+        self.wifi.connect().map_err(WifiError::ConnectionFailed)?;
+        self.wifi.wait_netif_up().map_err(WifiError::ConnectionFailed)?;
+
+        if let Some(ip) = self.ip_address() {
+            log::info!("WiFi connected, IP {}", ip);
+        }
+
+        self.event_queue.push(Event::NetworkUp);
+        Ok(())
+    }
+
+    pub fn disconnect(&mut self) -> Result<(), WifiError> {
+        if self.is_connected() {
+            self.wifi.disconnect()?;
+            self.event_queue.push(Event::NetworkDown);
+        }
+        Ok(())
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.wifi.is_connected().unwrap_or(false)
+    }
+
+    pub fn ip_address(&self) -> Option<Ipv4Addr> {
+        if !self.is_connected() {
+            return None;
+        }
+
+        self.wifi.wifi().sta_netif().get_ip_info().ok().map(|info| info.ip)
+    }
+
+    pub fn rssi(&self) -> Option<i8> {
+        if !self.is_connected() {
+            return None;
+        }
+
+        let mut ap_info = esp_idf_sys::wifi_ap_record_t::default();
+        match unsafe { esp_idf_sys::esp_wifi_sta_get_ap_info(&mut ap_info) } {
+            esp_idf_sys::ESP_OK => Some(ap_info.rssi),
+            _ => None,
+        }
+    }
+}
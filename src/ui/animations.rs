@@ -7,10 +7,53 @@ pub enum AnimationState {
     Completed,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Easing {
+    Linear,
+    EaseInQuad,
+    EaseOutQuad,
+    EaseInOutCubic,
+}
+
+impl Easing {
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+
+        match self {
+            Easing::Linear => t,
+            Easing::EaseInQuad => t * t,
+            Easing::EaseOutQuad => t * (2.0 - t),
+            Easing::EaseInOutCubic => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    let f = -2.0 * t + 2.0;
+                    1.0 - f * f * f / 2.0
+                }
+            },
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RepeatMode {
+    Once,
+    Loop,
+    PingPong,
+}
+
 pub trait Animation {
     fn update(&mut self, delta_time: Duration) -> bool;
     fn reset(&mut self);
     fn get_state(&self) -> AnimationState;
+
+    /// Whether the last `update` moved the animated value. Screens can skip redrawing (and
+    /// the bus traffic that comes with it) on frames where nothing visible happened.
+    fn value_changed(&self) -> bool;
+
+    fn is_looping(&self) -> bool {
+        false
+    }
 }
 
 pub struct FadeAnimation {
@@ -20,6 +63,11 @@ pub struct FadeAnimation {
     duration: Duration,
     elapsed: Duration,
     state: AnimationState,
+    changed: bool,
+    easing: Easing,
+    repeat: RepeatMode,
+    reversed: bool,
+    on_complete: Option<Box<dyn FnOnce() + Send>>,
 }
 
 impl FadeAnimation {
@@ -31,8 +79,32 @@ impl FadeAnimation {
             duration,
             elapsed: Duration::from_secs(0),
             state: AnimationState::Ready,
+            changed: false,
+            easing: Easing::Linear,
+            repeat: RepeatMode::Once,
+            reversed: false,
+            on_complete: None,
         }
-    } 
+    }
+
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    pub fn with_repeat(mut self, repeat: RepeatMode) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    /// Fires once, the first time the animation completes. The callback is consumed,
+    /// so `reset()` won't re-arm it; set a new one if the replay needs it too.
+    pub fn set_on_complete<F>(&mut self, callback: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.on_complete = Some(Box::new(callback));
+    }
 
     pub fn get_value(&self) -> f32 {
         self.current_value
@@ -41,7 +113,9 @@ impl FadeAnimation {
 
 impl Animation for FadeAnimation {
     fn update(&mut self, delta_time: Duration) -> bool {
-        match self.state {
+        let before = self.current_value;
+
+        let done = match self.state {
             AnimationState::Ready => {
                 self.state = AnimationState::Running;
                 self.elapsed = Duration::from_secs(0);
@@ -52,27 +126,60 @@ impl Animation for FadeAnimation {
                 self.elapsed += delta_time;
 
                 if self.elapsed >= self.duration {
-                    self.current_value = self.end_value;
-                    self.state = AnimationState::Completed;
-                    true
+                    match self.repeat {
+                        RepeatMode::Once => {
+                            self.current_value = self.end_value;
+                            self.state = AnimationState::Completed;
+                            if let Some(callback) = self.on_complete.take() {
+                                callback();
+                            }
+                            true
+                        },
+                        RepeatMode::Loop => {
+                            self.elapsed = Duration::from_secs(0);
+                            self.current_value = self.start_value;
+                            false
+                        },
+                        RepeatMode::PingPong => {
+                            self.elapsed = Duration::from_secs(0);
+                            self.reversed = !self.reversed;
+                            self.current_value = if self.reversed { self.end_value } else { self.start_value };
+                            false
+                        },
+                    }
                 } else {
-                    let progress = self.elapsed.as_secs_f32() / self.duration.as_secs_f32();
+                    let mut progress = self.easing.apply(self.elapsed.as_secs_f32() / self.duration.as_secs_f32());
+                    if self.reversed {
+                        progress = 1.0 - progress;
+                    }
                     self.current_value = self.start_value + (self.end_value - self.start_value) * progress;
                     false
                 }
             },
             AnimationState::Completed => true,
-        }
+        };
+
+        self.changed = self.current_value != before;
+        done
     }
 
     fn reset(&mut self) {
         self.state = AnimationState::Ready;
         self.elapsed = Duration::from_secs(0);
         self.current_value = self.start_value;
+        self.reversed = false;
     }
 
     fn get_state(&self) -> AnimationState {
-        self.state.clone()
+        self.state
+    }
+
+    fn value_changed(&self) -> bool {
+        self.changed
+    }
+
+    fn is_looping(&self) -> bool {
+        self.repeat != RepeatMode::Once
     }
 }
 
@@ -83,6 +190,11 @@ pub struct SlideAnimation {
     duration: Duration,
     elapsed: Duration,
     state: AnimationState,
+    changed: bool,
+    easing: Easing,
+    repeat: RepeatMode,
+    reversed: bool,
+    on_complete: Option<Box<dyn FnOnce() + Send>>,
 }
 
 impl SlideAnimation {
@@ -94,9 +206,32 @@ impl SlideAnimation {
             duration,
             elapsed: Duration::from_secs(0),
             state: AnimationState::Ready,
+            changed: false,
+            easing: Easing::Linear,
+            repeat: RepeatMode::Once,
+            reversed: false,
+            on_complete: None,
         }
     }
 
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    pub fn with_repeat(mut self, repeat: RepeatMode) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    /// Same one-shot semantics as `FadeAnimation::set_on_complete`.
+    pub fn set_on_complete<F>(&mut self, callback: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.on_complete = Some(Box::new(callback));
+    }
+
     pub fn get_position(&self) -> (i32, i32) {
         self.current_pos
     }
@@ -104,7 +239,9 @@ impl SlideAnimation {
 
 impl Animation for SlideAnimation {
     fn update(&mut self, delta_time: Duration) -> bool {
-        match self.state {
+        let before = self.current_pos;
+
+        let done = match self.state {
             AnimationState::Ready => {
                 self.state = AnimationState::Running;
                 self.elapsed = Duration::from_secs(0);
@@ -115,11 +252,32 @@ impl Animation for SlideAnimation {
                 self.elapsed += delta_time;
 
                 if self.elapsed >= self.duration {
-                    self.current_pos = self.end_pos;
-                    self.state = AnimationState::Completed;
-                    true
+                    match self.repeat {
+                        RepeatMode::Once => {
+                            self.current_pos = self.end_pos;
+                            self.state = AnimationState::Completed;
+                            if let Some(callback) = self.on_complete.take() {
+                                callback();
+                            }
+                            true
+                        },
+                        RepeatMode::Loop => {
+                            self.elapsed = Duration::from_secs(0);
+                            self.current_pos = self.start_pos;
+                            false
+                        },
+                        RepeatMode::PingPong => {
+                            self.elapsed = Duration::from_secs(0);
+                            self.reversed = !self.reversed;
+                            self.current_pos = if self.reversed { self.end_pos } else { self.start_pos };
+                            false
+                        },
+                    }
                 } else {
-                    let progress = self.elapsed.as_secs_f32() / self.duration.as_secs_f32();
+                    let mut progress = self.easing.apply(self.elapsed.as_secs_f32() / self.duration.as_secs_f32());
+                    if self.reversed {
+                        progress = 1.0 - progress;
+                    }
                     let x = self.start_pos.0 + ((self.end_pos.0 - self.start_pos.0) as f32 * progress) as i32;
                     let y = self.start_pos.1 + ((self.end_pos.1 - self.start_pos.1) as f32 * progress) as i32;
                     self.current_pos = (x, y);
@@ -127,17 +285,321 @@ impl Animation for SlideAnimation {
                 }
             },
             AnimationState::Completed => true,
-        }
+        };
+
+        self.changed = self.current_pos != before;
+        done
     }
 
     fn reset(&mut self) {
         self.state = AnimationState::Ready;
         self.elapsed = Duration::from_secs(0);
         self.current_pos = self.start_pos;
+        self.reversed = false;
+    }
+
+    fn get_state(&self) -> AnimationState {
+        self.state
+    }
+
+    fn value_changed(&self) -> bool {
+        self.changed
+    }
+
+    fn is_looping(&self) -> bool {
+        self.repeat != RepeatMode::Once
+    }
+}
+
+pub struct ScaleAnimation {
+    start_scale: f32,
+    end_scale: f32,
+    current_scale: f32,
+    duration: Duration,
+    elapsed: Duration,
+    state: AnimationState,
+    changed: bool,
+    easing: Easing,
+    repeat: RepeatMode,
+    reversed: bool,
+    on_complete: Option<Box<dyn FnOnce() + Send>>,
+}
+
+impl ScaleAnimation {
+    pub fn new(start_scale: f32, end_scale: f32, duration: Duration) -> Self {
+        let start_scale = start_scale.max(0.0);
+
+        Self {
+            start_scale,
+            end_scale: end_scale.max(0.0),
+            current_scale: start_scale,
+            duration,
+            elapsed: Duration::from_secs(0),
+            state: AnimationState::Ready,
+            changed: false,
+            easing: Easing::Linear,
+            repeat: RepeatMode::Once,
+            reversed: false,
+            on_complete: None,
+        }
+    }
+
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    pub fn with_repeat(mut self, repeat: RepeatMode) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    /// Same one-shot semantics as `FadeAnimation::set_on_complete`.
+    pub fn set_on_complete<F>(&mut self, callback: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.on_complete = Some(Box::new(callback));
+    }
+
+    pub fn get_scale(&self) -> f32 {
+        self.current_scale
+    }
+}
+
+impl Animation for ScaleAnimation {
+    fn update(&mut self, delta_time: Duration) -> bool {
+        let before = self.current_scale;
+
+        let done = match self.state {
+            AnimationState::Ready => {
+                self.state = AnimationState::Running;
+                self.elapsed = Duration::from_secs(0);
+                self.current_scale = self.start_scale;
+                false
+            },
+            AnimationState::Running => {
+                self.elapsed += delta_time;
+
+                if self.elapsed >= self.duration {
+                    match self.repeat {
+                        RepeatMode::Once => {
+                            self.current_scale = self.end_scale;
+                            self.state = AnimationState::Completed;
+                            if let Some(callback) = self.on_complete.take() {
+                                callback();
+                            }
+                            true
+                        },
+                        RepeatMode::Loop => {
+                            self.elapsed = Duration::from_secs(0);
+                            self.current_scale = self.start_scale;
+                            false
+                        },
+                        RepeatMode::PingPong => {
+                            self.elapsed = Duration::from_secs(0);
+                            self.reversed = !self.reversed;
+                            self.current_scale = if self.reversed { self.end_scale } else { self.start_scale };
+                            false
+                        },
+                    }
+                } else {
+                    let mut progress = self.easing.apply(self.elapsed.as_secs_f32() / self.duration.as_secs_f32());
+                    if self.reversed {
+                        progress = 1.0 - progress;
+                    }
+                    //easing curves that overshoot must not turn into a negative scale
+                    self.current_scale = (self.start_scale + (self.end_scale - self.start_scale) * progress).max(0.0);
+                    false
+                }
+            },
+            AnimationState::Completed => true,
+        };
+
+        self.changed = self.current_scale != before;
+        done
+    }
+
+    fn reset(&mut self) {
+        self.state = AnimationState::Ready;
+        self.elapsed = Duration::from_secs(0);
+        self.current_scale = self.start_scale;
+        self.reversed = false;
+    }
+
+    fn get_state(&self) -> AnimationState {
+        self.state
+    }
+
+    fn value_changed(&self) -> bool {
+        self.changed
+    }
+
+    fn is_looping(&self) -> bool {
+        self.repeat != RepeatMode::Once
+    }
+}
+
+pub struct BlinkAnimation {
+    half_period: Duration,
+    elapsed: Duration,
+    cycles: Option<u32>,
+    toggles: u32,
+    visible: bool,
+    state: AnimationState,
+    changed: bool,
+}
+
+impl BlinkAnimation {
+    //blinks forever by default, one cycle is a full off+on at the given frequency
+    pub fn new(frequency_hz: f32) -> Self {
+        Self {
+            half_period: Duration::from_secs_f32(0.5 / frequency_hz.max(0.01)),
+            elapsed: Duration::from_secs(0),
+            cycles: None,
+            toggles: 0,
+            visible: true,
+            state: AnimationState::Ready,
+            changed: false,
+        }
+    }
+
+    pub fn with_cycles(mut self, cycles: u32) -> Self {
+        self.cycles = Some(cycles.max(1));
+        self
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+}
+
+impl Animation for BlinkAnimation {
+    fn update(&mut self, delta_time: Duration) -> bool {
+        let before = self.visible;
+
+        let done = match self.state {
+            AnimationState::Ready => {
+                self.state = AnimationState::Running;
+                self.elapsed = Duration::from_secs(0);
+                self.toggles = 0;
+                self.visible = true;
+                false
+            },
+            AnimationState::Running => {
+                self.elapsed += delta_time;
+
+                while self.elapsed >= self.half_period {
+                    self.elapsed -= self.half_period;
+                    self.visible = !self.visible;
+                    self.toggles += 1;
+
+                    //every cycle ends back on, so a finished blink leaves the widget showing
+                    if let Some(cycles) = self.cycles {
+                        if self.toggles >= cycles * 2 {
+                            self.visible = true;
+                            self.state = AnimationState::Completed;
+                            self.changed = self.visible != before;
+                            return true;
+                        }
+                    }
+                }
+
+                false
+            },
+            AnimationState::Completed => true,
+        };
+
+        self.changed = self.visible != before;
+        done
+    }
+
+    fn reset(&mut self) {
+        self.state = AnimationState::Ready;
+        self.elapsed = Duration::from_secs(0);
+        self.toggles = 0;
+        self.visible = true;
     }
 
     fn get_state(&self) -> AnimationState {
-        self.state.clone()
+        self.state
+    }
+
+    fn value_changed(&self) -> bool {
+        self.changed
+    }
+
+    fn is_looping(&self) -> bool {
+        self.cycles.is_none()
+    }
+}
+
+pub struct AnimationSequence {
+    steps: Vec<Box<dyn Animation + Send>>,
+    active_index: usize,
+    changed: bool,
+}
+
+impl AnimationSequence {
+    pub fn new(steps: Vec<Box<dyn Animation + Send>>) -> Self {
+        Self {
+            steps,
+            active_index: 0,
+            changed: false,
+        }
+    }
+
+    pub fn active_index(&self) -> usize {
+        self.active_index
+    }
+}
+
+impl Animation for AnimationSequence {
+    fn update(&mut self, delta_time: Duration) -> bool {
+        let Some(step) = self.steps.get_mut(self.active_index) else {
+            self.changed = false;
+            return true;
+        };
+
+        let done = step.update(delta_time);
+        self.changed = step.value_changed();
+
+        if !done {
+            return false;
+        }
+
+        if self.active_index + 1 < self.steps.len() {
+            self.active_index += 1;
+            false
+        } else {
+            true
+        }
+    }
+
+    fn reset(&mut self) {
+        for step in &mut self.steps {
+            step.reset();
+        }
+        self.active_index = 0;
+    }
+
+    fn get_state(&self) -> AnimationState {
+        match self.steps.get(self.active_index) {
+            Some(step) if self.active_index == 0 => step.get_state(),
+            Some(step) => match step.get_state() {
+                AnimationState::Ready => AnimationState::Running,
+                state => state,
+            },
+            None => AnimationState::Completed,
+        }
+    }
+
+    fn value_changed(&self) -> bool {
+        self.changed
+    }
+
+    fn is_looping(&self) -> bool {
+        self.steps.iter().any(|step| step.is_looping())
     }
 }
 
@@ -161,6 +623,10 @@ impl AnimationManager {
         self.animations.push(Box::new(animation));
     }
 
+    pub fn add_sequence(&mut self, steps: Vec<Box<dyn Animation + Send>>) {
+        self.animations.push(Box::new(AnimationSequence::new(steps)));
+    }
+
     pub fn update(&mut self) -> bool {
         let now = Instant::now();
         let delta = now.duration_since(self.last_update);
@@ -170,7 +636,7 @@ impl AnimationManager {
 
         for animation in &mut self.animations {
             let completed = animation.update(delta);
-            if !completed {
+            if !completed || animation.is_looping() {
                 all_completed = false;
             }
         }
@@ -178,9 +644,89 @@ impl AnimationManager {
         all_completed
     }
 
+    //true if anything moved in the last update, nothing needs redrawing otherwise
+    pub fn value_changed(&self) -> bool {
+        self.animations.iter().any(|animation| animation.value_changed())
+    }
+
     pub fn reset_all(&mut self) {
         for animation in &mut self.animations {
             animation.reset();
         }
     }
 }
+
+impl Default for AnimationManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    const HALF: Duration = Duration::from_millis(50);
+    const FULL: Duration = Duration::from_millis(100);
+
+    //the first update only starts the animation, time counts from the second
+    fn fade_at_half(easing: Easing) -> f32 {
+        let mut fade = FadeAnimation::new(0.0, 100.0, FULL).with_easing(easing);
+        fade.update(Duration::ZERO);
+        fade.update(HALF);
+        fade.get_value()
+    }
+
+    #[test]
+    fn ease_out_quad_is_past_the_midpoint_at_half_time() {
+        assert_eq!(fade_at_half(Easing::Linear), 50.0);
+        assert!(fade_at_half(Easing::EaseOutQuad) > 50.0);
+    }
+
+    #[test]
+    fn ease_out_quad_slide_is_past_the_midpoint_at_half_time() {
+        let mut slide = SlideAnimation::new((0, 0), (100, 0), FULL).with_easing(Easing::EaseOutQuad);
+        slide.update(Duration::ZERO);
+        slide.update(HALF);
+
+        assert!(slide.get_position().0 > 50);
+    }
+
+    fn fade_flagging_completion() -> (FadeAnimation, Arc<AtomicBool>) {
+        let completed = Arc::new(AtomicBool::new(false));
+        let flag = completed.clone();
+        let mut fade = FadeAnimation::new(0.0, 1.0, FULL);
+        fade.set_on_complete(move || flag.store(true, Ordering::Relaxed));
+        (fade, completed)
+    }
+
+    //add_sequence wraps the steps in exactly this, the manager itself only adds wall-clock deltas
+    #[test]
+    fn sequence_holds_the_second_fade_until_the_first_completes() {
+        let (first, first_done) = fade_flagging_completion();
+        let (second, second_done) = fade_flagging_completion();
+        let mut sequence = AnimationSequence::new(vec![Box::new(first), Box::new(second)]);
+
+        sequence.update(Duration::ZERO);
+        assert!(!sequence.update(HALF));
+        assert_eq!(sequence.active_index(), 0);
+
+        //run side by side, the equally long second fade would finish here too
+        assert!(!sequence.update(HALF));
+        assert!(first_done.load(Ordering::Relaxed));
+        assert!(!second_done.load(Ordering::Relaxed));
+        assert_eq!(sequence.active_index(), 1);
+
+        sequence.update(Duration::ZERO);
+        assert!(!sequence.update(FULL - Duration::from_millis(1)));
+        assert!(!second_done.load(Ordering::Relaxed));
+
+        assert!(sequence.update(Duration::from_millis(1)));
+        assert!(second_done.load(Ordering::Relaxed));
+
+        sequence.reset();
+        assert_eq!(sequence.active_index(), 0);
+    }
+}
@@ -0,0 +1,143 @@
+use crate::drivers::display::{DisplayBackend, DisplayError};
+use crate::system::events::{Event, EventQueue};
+use crate::system::scheduler::Scheduler;
+use crate::ui::framework::{Screen, ScreenContext};
+use std::sync::{Arc, Mutex};
+
+pub trait App {
+    fn id(&self) -> &str;
+    fn on_event(&mut self, event: &Event) -> bool;
+    fn render(&self, display: &dyn DisplayBackend) -> Result<(), DisplayError>;
+
+    fn on_launch(&mut self, _context: &mut ScreenContext) {}
+
+    fn on_close(&mut self, _context: &mut ScreenContext) {}
+}
+
+//lets any existing screen run as an app without rewriting it
+pub struct ScreenApp<S: Screen> {
+    id: String,
+    screen: S,
+}
+
+impl<S: Screen> ScreenApp<S> {
+    pub fn new(id: &str, screen: S) -> Self {
+        Self {
+            id: id.to_string(),
+            screen,
+        }
+    }
+}
+
+impl<S: Screen> App for ScreenApp<S> {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn on_event(&mut self, event: &Event) -> bool {
+        self.screen.handle_event(event)
+    }
+
+    fn render(&self, _display: &dyn DisplayBackend) -> Result<(), DisplayError> {
+        //the screen already holds its own handle to the display
+        self.screen.draw()
+    }
+
+    fn on_launch(&mut self, context: &mut ScreenContext) {
+        self.screen.on_enter(context);
+    }
+
+    fn on_close(&mut self, context: &mut ScreenContext) {
+        self.screen.on_exit(context);
+    }
+}
+
+pub struct AppManager {
+    apps: Vec<Box<dyn App + Send>>,
+    active: Option<usize>,
+    //separate from the screens' context, an app and the screen under it are left at different times
+    context: ScreenContext,
+    event_queue: Arc<EventQueue>,
+}
+
+impl AppManager {
+    pub fn new(event_queue: Arc<EventQueue>) -> Self {
+        Self {
+            apps: Vec::new(),
+            active: None,
+            context: ScreenContext::new(event_queue.clone()),
+            event_queue,
+        }
+    }
+
+    pub fn set_scheduler(&mut self, scheduler: Arc<Mutex<Scheduler>>) {
+        self.context.set_scheduler(scheduler);
+    }
+
+    pub fn register<A>(&mut self, app: A)
+    where
+        A: App + Send + 'static,
+    {
+        if self.apps.iter().any(|existing| existing.id() == app.id()) {
+            log::warn!("App '{}' is already registered", app.id());
+            return;
+        }
+
+        self.apps.push(Box::new(app));
+    }
+
+    pub fn launch(&mut self, id: &str) -> bool {
+        let index = match self.apps.iter().position(|app| app.id() == id) {
+            Some(index) => index,
+            None => {
+                log::warn!("No app registered as '{}'", id);
+                return false;
+            }
+        };
+
+        if self.active == Some(index) {
+            return true;
+        }
+
+        self.close();
+
+        self.active = Some(index);
+        self.apps[index].on_launch(&mut self.context);
+        self.event_queue.push(Event::AppLaunched(id.to_string()));
+        true
+    }
+
+    pub fn close(&mut self) -> bool {
+        match self.active.take() {
+            Some(index) => {
+                self.apps[index].on_close(&mut self.context);
+                self.context.cancel_tasks();
+                self.event_queue.push(Event::AppClosed(self.apps[index].id().to_string()));
+                true
+            },
+            None => false,
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.active.is_some()
+    }
+
+    pub fn active_id(&self) -> Option<&str> {
+        self.active.map(|index| self.apps[index].id())
+    }
+
+    pub fn handle_event(&mut self, event: &Event) -> bool {
+        match self.active {
+            Some(index) => self.apps[index].on_event(event),
+            None => false,
+        }
+    }
+
+    pub fn render(&self, display: &dyn DisplayBackend) -> Result<(), DisplayError> {
+        match self.active {
+            Some(index) => self.apps[index].render(display),
+            None => Ok(()),
+        }
+    }
+}
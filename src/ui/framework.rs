@@ -1,17 +1,107 @@
-use crate::drivers::display::{DisplayManager, DisplayError, TextSize};
-use crate::system::events::{Event, EventHandler};
-use embedded_graphics::prelude::*;
+use crate::drivers::display::{font_metrics, measure_text, text_width, Canvas, DisplayBackend, DisplayError, TargetCanvas, TextSize};
+use crate::system::events::{ButtonMap, Event, EventQueue};
+use crate::system::power::IdleDimmer;
+use crate::system::time::FrameTimer;
+use crate::system::clock::SystemClock;
+use crate::system::scheduler::Scheduler;
+use crate::ui::apps::{App, AppManager};
+use crate::ui::screensaver::Screensaver;
+use crate::ui::screens::error::ErrorScreen;
+use crate::ui::transition::{ScreenTransition, Transition, DEFAULT_TRANSITION_DURATION};
+use embedded_graphics::{pixelcolor::BinaryColor, prelude::{DrawTarget, Point}};
 use std::sync::{Arc, Mutex};
-use std::collections::VecDeque;
 use std::any::Any;
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
 
 pub trait Widget {
-    fn draw(&self, display: &DisplayManager) -> Result<(), DisplayError>;
+    fn draw(&self, display: &dyn Canvas) -> Result<(), DisplayError>;
     fn handle_event(&mut self, event: &Event) -> bool;
     fn get_bounds(&self) -> Rectangle;
+
+    //widgets that never move (a fixed overlay, say) can leave this out
+    fn set_position(&mut self, _x: i32, _y: i32) {}
+
+    fn is_focusable(&self) -> bool {
+        false
+    }
+
+    fn set_focused(&mut self, _focused: bool) {}
+
+    /// For containers: moves focus on to the next interactive child. Returns false when
+    /// there's none left inside, and the screen moves focus on to the next widget instead.
+    fn focus_next(&mut self) -> bool {
+        false
+    }
+
+    fn focus_prev(&mut self) -> bool {
+        false
+    }
+
+    /// Select is being held down on this widget. Set by the screen on the focused widget
+    /// when Select arrives and cleared on the release that follows, so every interactive
+    /// widget shows the same press feedback.
+    fn set_pressed(&mut self, _pressed: bool) {}
+
+    //drops any half-finished interaction, called when the screen holding the widget is left
+    fn reset(&mut self) {}
+}
+
+//arriving from below, a container should start on its last interactive child rather than its first
+fn focus_last_inside<W: Widget + ?Sized>(widget: &mut W) {
+    while widget.focus_next() {}
+}
+
+fn draw_focus_ring(display: &dyn Canvas, bounds: &Rectangle) -> Result<(), DisplayError> {
+    display.draw_rectangle(bounds.x - 2, bounds.y - 2, bounds.width + 4, bounds.height + 4, false)
+}
+
+//sits just inside the focus ring, together they read as one thick border while select is held
+fn draw_press_ring(display: &dyn Canvas, bounds: &Rectangle) -> Result<(), DisplayError> {
+    display.draw_rectangle(bounds.x - 1, bounds.y - 1, bounds.width + 2, bounds.height + 2, false)
+}
+
+//draws a widget onto any embedded-graphics target, e.g. a simulator window or a different panel
+pub fn draw_onto<W, D>(widget: &W, target: &mut D) -> Result<(), D::Error>
+where
+    W: Widget + ?Sized,
+    D: DrawTarget<Color = BinaryColor>,
+{
+    let canvas = TargetCanvas::new(target);
+    let result = Widget::draw(widget, &canvas);
+
+    if let Some(e) = canvas.into_error() {
+        return Err(e);
+    }
+
+    //anything left is our own validation (e.g. a malformed bitmap), the target itself is fine
+    if let Err(e) = result {
+        log::warn!("Widget draw failed: {}", e);
+    }
+
+    Ok(())
+}
+
+macro_rules! impl_drawable {
+    ($($widget:ty),* $(,)?) => {
+        $(
+            impl embedded_graphics::Drawable for $widget {
+                type Color = BinaryColor;
+                type Output = ();
+
+                fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+                where
+                    D: DrawTarget<Color = BinaryColor>,
+                {
+                    draw_onto(self, target)
+                }
+            }
+        )*
+    };
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Rectangle {
     pub x: i32,
     pub y: i32,
@@ -19,52 +109,213 @@ pub struct Rectangle {
     pub height: u32,
 }
 
+//right and bottom edges are exclusive, so rectangles that only touch don't overlap
+impl Rectangle {
+    pub fn new(x: i32, y: i32, width: u32, height: u32) -> Self {
+        Self { x, y, width, height }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    fn right(&self) -> i32 {
+        self.x + self.width as i32
+    }
+
+    fn bottom(&self) -> i32 {
+        self.y + self.height as i32
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        point.x >= self.x && point.x < self.right()
+            && point.y >= self.y && point.y < self.bottom()
+    }
+
+    pub fn intersects(&self, other: &Rectangle) -> bool {
+        if self.is_empty() || other.is_empty() {
+            return false;
+        }
+
+        self.x < other.right() && other.x < self.right()
+            && self.y < other.bottom() && other.y < self.bottom()
+    }
+
+    //smallest rectangle covering both, an empty side doesn't stretch it
+    pub fn union(&self, other: &Rectangle) -> Rectangle {
+        if other.is_empty() {
+            return self.clone();
+        }
+        if self.is_empty() {
+            return other.clone();
+        }
+
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+
+        Rectangle {
+            x,
+            y,
+            width: (self.right().max(other.right()) - x) as u32,
+            height: (self.bottom().max(other.bottom()) - y) as u32,
+        }
+    }
+}
+
+/// Where a widget goes, in pixels or as fractions (0.0 to 1.0) of the panel's width and
+/// height, so a layout written for 128x64 still lands sensibly on a 128x32 panel.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Position {
+    Absolute(i32, i32),
+    Relative(f32, f32),
+}
+
+impl Position {
+    /// Pixel coordinates on a `width` x `height` panel. Relative values are rounded to the
+    /// nearest pixel with halves rounded away from zero, so `Relative(0.5, 0.5)` on 128x63
+    /// is (64, 32). Fractions outside 0.0 to 1.0 aren't clamped.
+    pub fn resolve(&self, width: u32, height: u32) -> (i32, i32) {
+        match *self {
+            Position::Absolute(x, y) => (x, y),
+            Position::Relative(x, y) => (scale(x, width), scale(y, height)),
+        }
+    }
+}
+
+/// A widget's width and height, the sizing counterpart to `Position`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Extent {
+    Absolute(u32, u32),
+    Relative(f32, f32),
+}
+
+impl Extent {
+    //rounds like Position::resolve, a negative fraction comes out as 0
+    pub fn resolve(&self, width: u32, height: u32) -> (u32, u32) {
+        match *self {
+            Extent::Absolute(w, h) => (w, h),
+            Extent::Relative(w, h) => (scale(w, width).max(0) as u32, scale(h, height).max(0) as u32),
+        }
+    }
+}
+
+fn scale(fraction: f32, dimension: u32) -> i32 {
+    (fraction * dimension as f32).round() as i32
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Alignment {
+    Left,
+    Center,
+    Right,
+}
+
 pub struct Label {
     text: String,
     position: Point,
     size: TextSize,
+    alignment: Alignment,
+    align_width: Option<u32>,
+    inverted: bool,
     bounds: Rectangle,
+    //set by positioned, resolved again against the panel on every draw
+    anchor: Option<Position>,
+    panel: Cell<(u32, u32)>,
 }
 
 impl Label {
-    pub fn new(text: &str, x: i32, y: i32, size: TextSize) -> Self {
-        let char_width = match size {
-            TextSize::Small => 5,
-            TextSize::Normal => 6,
-            TextSize::Large => 8,
-        };
+    //follows the panel if it's rotated or swapped, until set_position pins it in pixels
+    pub fn positioned(text: &str, position: Position, size: TextSize, display: &dyn Canvas) -> Self {
+        let (x, y) = position.resolve(display.width(), display.height());
+        let mut label = Self::new(text, x, y, size);
+        label.anchor = Some(position);
+        label.panel.set((display.width(), display.height()));
+        label
+    }
 
-        let width = text.len() as u32 * char_width;
-        let height = match size {
-            TextSize::Small => 8,
-            TextSize::Normal => 10,
-            TextSize::Large => 16
-        };
+    pub fn new(text: &str, x: i32, y: i32, size: TextSize) -> Self {
+        let (width, height) = measure_text(text, size);
 
         Self {
             text: text.to_string(),
             position: Point::new(x, y),
             size,
+            alignment: Alignment::Left,
+            align_width: None,
+            inverted: false,
             bounds: Rectangle {x, y, width, height },
+            anchor: None,
+            panel: Cell::new((0, 0)),
         }
     }
 
+    //aligns the text inside a fixed-width region starting at the label's x, which also becomes its bounds
+    pub fn with_alignment(mut self, alignment: Alignment, width: u32) -> Self {
+        self.alignment = alignment;
+        self.align_width = Some(width);
+        self.bounds.width = width;
+        self
+    }
+
+    //dark text on a lit box, for sitting on top of a filled shape
+    pub fn set_inverted(&mut self, inverted: bool) {
+        self.inverted = inverted;
+    }
+
     pub fn set_text(&mut self, text: &str) {
         self.text = text.to_string();
 
-        let char_width = match self.size {
-            TextSize::Small => 5,
-            TextSize::Normal => 6,
-            TextSize::Large => 8,
+        let (width, height) = measure_text(text, self.size);
+        self.bounds.height = height;
+        if self.align_width.is_none() {
+            self.bounds.width = width;
+        }
+    }
+
+    fn text_width(&self) -> u32 {
+        text_width(&self.text, self.size)
+    }
+
+    //the top left corner on the panel size last drawn to
+    fn origin(&self) -> Point {
+        match self.anchor {
+            Some(anchor) => {
+                let (width, height) = self.panel.get();
+                let (x, y) = anchor.resolve(width, height);
+                Point::new(x, y)
+            },
+            None => self.position,
+        }
+    }
+
+    //for widgets that place their label themselves, align_width stands in for the label's own
+    fn draw_at(&self, display: &dyn Canvas, origin: Point, align_width: Option<u32>) -> Result<(), DisplayError> {
+        let slack = match align_width {
+            Some(width) => width as i32 - self.text_width() as i32,
+            None => 0,
+        };
+
+        let x = match self.alignment {
+            Alignment::Left => origin.x,
+            Alignment::Center => origin.x + slack / 2,
+            Alignment::Right => origin.x + slack,
         };
 
-        self.bounds.width = text.len() as u32 * char_width;
+        if self.inverted {
+            display.draw_text_inverted(&self.text, x, origin.y, self.size)
+        } else {
+            display.draw_text(&self.text, x, origin.y, self.size)
+        }
     }
 }
 
 impl Widget for Label {
-    fn draw(&self, display: &DisplayManager) -> Result<(), DisplayError> {
-        display.draw_text(&self.text, self.position.x, self.position.y, self.size.clone())
+    fn draw(&self, display: &dyn Canvas) -> Result<(), DisplayError> {
+        if self.anchor.is_some() {
+            self.panel.set((display.width(), display.height()));
+        }
+
+        self.draw_at(display, self.origin(), self.align_width)
     }
 
     fn handle_event(&mut self, _event: &Event) -> bool {
@@ -73,99 +324,103 @@ impl Widget for Label {
     }
 
     fn get_bounds(&self) -> Rectangle {
-        self.bounds.clone()
+        let origin = self.origin();
+        Rectangle { x: origin.x, y: origin.y, ..self.bounds.clone() }
+    }
+
+    fn set_position(&mut self, x: i32, y: i32) {
+        self.anchor = None;
+        self.position = Point::new(x, y);
+        self.bounds.x = x;
+        self.bounds.y = y;
     }
 }
 
-pub struct Button {
-    label: Label,
+pub struct WrappedLabel {
+    text: String,
+    lines: Vec<String>,
+    position: Point,
+    size: TextSize,
+    max_width: u32,
     bounds: Rectangle,
-    pressed: bool,
-    on_click: Option<Box<dyn Fn() + Send>>,
 }
 
-impl Button {
-    pub fn new(text: &str, x: i32, y: i32, width: u32, height: u32) -> Self {
-        let label_x = x + (width as i32 - text.len() as i32 * 6) / 2;
-        let label_y = y + (height as i32 - 10) / 2;
+impl WrappedLabel {
+    pub fn new(text: &str, x: i32, y: i32, max_width: u32, size: TextSize) -> Self {
+        let mut label = Self {
+            text: String::new(),
+            lines: Vec::new(),
+            position: Point::new(x, y),
+            size,
+            max_width,
+            bounds: Rectangle { x, y, width: 0, height: 0 },
+        };
 
-        Self {
-            label: Label::new(text, label_x, label_y, TextSize::Normal),
-            bounds: Rectangle { x, y, width, height },
-            pressed: false,
-            on_click: None,
-        }
+        label.set_text(text);
+        label
     }
 
-    pub fn set_on_click<F>(&mut self, callback: F)
-    where
-        F: Fn() + Send + 'static,
-    {
-        self.on_click = Some(Box::new(callback));
-    }
-}
+    pub fn set_text(&mut self, text: &str) {
+        self.text = text.to_string();
 
-impl Widget for Button {
-    fn draw(&self, display: &DisplayManager) -> Result<(), DisplayError> {
-        display.draw_rectangle(
-            self.bounds.x,
-            self.bounds.y,
-            self.bounds.width,
-            self.bounds.height,
-            self.pressed,
-        )?;
+        let (char_width, line_height) = font_metrics(self.size);
 
-        self.label.draw(display)
+        let max_chars = (self.max_width / char_width).max(1) as usize;
+        self.lines = Self::wrap(&self.text, max_chars);
+
+        let widest = self.lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+        self.bounds.width = widest as u32 * char_width;
+        self.bounds.height = self.lines.len() as u32 * line_height;
     }
 
-    fn handle_event(&mut self, event: &Event) -> bool {
-        match event {
-            Event::ButtonPressed(pin) if *pin == 26 => {
-                self.pressed = true;
-                true
-            },
-            Event::ButtonReleased(pin) if *pin == 26 => {
-                self.pressed = false;
-                if let Some(callback) = &self.on_click {
-                    callback();
+    fn wrap(text: &str, max_chars: usize) -> Vec<String> {
+        let mut lines = Vec::new();
+        let mut current = String::new();
+
+        for word in text.split_whitespace() {
+            let mut word: Vec<char> = word.chars().collect();
+
+            //words longer than a whole line get hard-split
+            while word.len() > max_chars {
+                if !current.is_empty() {
+                    lines.push(std::mem::take(&mut current));
                 }
-                true
-            },
-            _ => false,
-        }
-    }
+                let rest = word.split_off(max_chars);
+                lines.push(word.into_iter().collect());
+                word = rest;
+            }
 
-    fn get_bounds(&self) -> Rectangle {
-        self.bounds.clone()
-    }
-}
+            let word: String = word.into_iter().collect();
+            let current_len = current.chars().count();
 
-pub struct ProgressBar {
-    bounds: Rectangle,
-    progress: u8,
-}
+            if current.is_empty() {
+                current = word;
+            } else if current_len + 1 + word.chars().count() <= max_chars {
+                current.push(' ');
+                current.push_str(&word);
+            } else {
+                lines.push(std::mem::replace(&mut current, word));
+            }
+        }
 
-impl ProgressBar {
-    pub fn new(x: i32, y: i32, width: u32, progress: u8) -> Self {
-        Self {
-            bounds: Rectangle { x, y, width, height: 8 },
-            progress: progress.min(100),
+        if !current.is_empty() {
+            lines.push(current);
         }
-    }
 
-    pub fn set_progress(&mut self, progress: u8) {
-        self.progress = progress.min(100);
+        lines
     }
 }
 
-impl Widget for ProgressBar {
-    fn draw(&self, display: &DisplayManager) -> Result<(), DisplayError> {
-        display.draw_progress_bar(
-            self.bounds.x,
-            self.bounds.y,
-            self.bounds.width,
-            self.progress
-        )
+impl Widget for WrappedLabel {
+    fn draw(&self, display: &dyn Canvas) -> Result<(), DisplayError> {
+        let (_, line_height) = font_metrics(self.size);
+
+        for (index, line) in self.lines.iter().enumerate() {
+            let y = self.position.y + index as i32 * line_height as i32;
+            display.draw_text(line, self.position.x, y, self.size)?;
+        }
+
+        Ok(())
     }
 
     fn handle_event(&mut self, _event: &Event) -> bool {
@@ -175,123 +430,2982 @@ impl Widget for ProgressBar {
     fn get_bounds(&self) -> Rectangle {
         self.bounds.clone()
     }
-}
 
-pub trait Screen: Any {
-    fn draw(&self) -> Result<(), DisplayError>;
-    fn handle_event(&mut self, event: &Event) -> bool;
+    fn set_position(&mut self, x: i32, y: i32) {
+        self.position = Point::new(x, y);
+        self.bounds.x = x;
+        self.bounds.y = y;
+    }
 }
 
-pub struct DefaultScreen {
-    widgets: Vec<Box<dyn Widget + Send>>,
-    display: Arc<DisplayManager>,
+pub struct ScrollingLabel {
+    text: String,
+    size: TextSize,
+    bounds: Rectangle,
+    offset: f32,
+    speed: f32,
+    gap: u32,
+    last_tick: Instant,
 }
 
-impl DefaultScreen {
-    pub fn new(display: Arc<DisplayManager>) -> Self {
+impl ScrollingLabel {
+    pub fn new(text: &str, x: i32, y: i32, max_width: u32, size: TextSize) -> Self {
+        let (_, height) = font_metrics(size);
+
         Self {
-            widgets: Vec::new(),
-            display,
+            text: text.to_string(),
+            size,
+            bounds: Rectangle { x, y, width: max_width, height },
+            offset: 0.0,
+            speed: 20.0,
+            gap: 24,
+            last_tick: Instant::now(),
         }
     }
 
-    pub fn add_widget<W>(&mut self, widget: W)
-    where
-        W: Widget + Send + 'static,
-    {
-        self.widgets.push(Box::new(widget));
+    //pixels per second
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed.max(0.0);
+        self
     }
 
-}
-
-impl Screen for DefaultScreen {
-    fn draw(&self) -> Result<(), DisplayError> {
-        self.display.clear()?;
+    pub fn with_gap(mut self, gap: u32) -> Self {
+        self.gap = gap;
+        self
+    }
 
-        for widget in &self.widgets {
-            widget.draw(&self.display)?;
-        }
+    pub fn set_text(&mut self, text: &str) {
+        self.text = text.to_string();
+        self.offset = 0.0;
+    }
 
-        self.display.flush()
+    fn text_width(&self) -> u32 {
+        text_width(&self.text, self.size)
     }
 
-    fn handle_event(&mut self, event: &Event) -> bool {
-        let mut handled = false;
+    pub fn needs_scroll(&self) -> bool {
+        self.text_width() > self.bounds.width
+    }
 
-        for widget in &mut self.widgets {
-            if widget.handle_event(event) {
-                handled = true;
-                break;
-            }
+    //returns true when the visible pixel offset moved and the label should be redrawn
+    pub fn update(&mut self, delta: Duration) -> bool {
+        if !self.needs_scroll() {
+            return false;
         }
 
-        if handled {
-            let _ = self.draw();
-        }
+        let cycle = (self.text_width() + self.gap) as f32;
+        let before = self.offset as i32;
 
-        handled
-    }
-}
+        self.offset = (self.offset + self.speed * delta.as_secs_f32()) % cycle;
 
-pub struct ScreenManager {
-    screens: Vec<Box<dyn Screen + Send>>,
-    current_screen: usize,
-    display: Arc<DisplayManager>,
-    event_queue: Arc<Mutex<VecDeque<Event>>>,
+        self.offset as i32 != before
+    }
 }
 
-impl ScreenManager {
-    pub fn new(display: Arc<DisplayManager>, event_queue: Arc<Mutex<VecDeque<Event>>>) -> Self {
-        Self {
-            screens: Vec::new(),
-            current_screen: 0,
-            display,
-            event_queue,
+impl Widget for ScrollingLabel {
+    fn draw(&self, display: &dyn Canvas) -> Result<(), DisplayError> {
+        if !self.needs_scroll() {
+            return display.draw_text(&self.text, self.bounds.x, self.bounds.y, self.size);
         }
-    }
 
-    pub fn add_screen<S>(&mut self, screen: S)
-    where 
-        S: Screen + Send + 'static,
-    {
-        self.screens.push(Box::new(screen));
+        let cycle = (self.text_width() + self.gap) as i32;
+        let x = self.bounds.x - self.offset as i32;
+
+        //second copy trails the first by one cycle so the wrap-around is seamless
+        display.draw_text_clipped(&self.text, x, self.bounds.y, self.size, self.bounds.x, self.bounds.width)?;
+        display.draw_text_clipped(&self.text, x + cycle, self.bounds.y, self.size, self.bounds.x, self.bounds.width)
     }
 
-    pub fn switch_to_screen(&mut self, index: usize) -> Result<(), DisplayError> {
-        if index < self.screens.len() {
-            self.current_screen = index;
-            self.screens[self.current_screen].draw()?;
+    fn handle_event(&mut self, event: &Event) -> bool {
+        match event {
+            Event::SystemTick => {
+                let now = Instant::now();
+                let delta = now.duration_since(self.last_tick);
+                self.last_tick = now;
+                self.update(delta)
+            },
+            _ => false,
         }
-        Ok(())
     }
 
-    pub fn process_events(&mut self) -> Result<(), DisplayError> {
-        let mut queue = self.event_queue.lock().unwrap();
-
-        while let Some(event) = queue.pop_front() {
-            self.screens[self.current_screen].handle_event(&event);
-        }
+    fn get_bounds(&self) -> Rectangle {
+        self.bounds.clone()
+    }
 
-        Ok(())
+    fn set_position(&mut self, x: i32, y: i32) {
+        self.bounds.x = x;
+        self.bounds.y = y;
     }
+}
 
-    pub fn get_screen_as_mut<T: 'static>(&mut self) -> Option<&mut T> {
-        let screen_box = &mut self.screens[self.current_screen];
+pub const DEFAULT_CONFIRM_WINDOW: Duration = Duration::from_secs(3);
+const BUTTON_CORNER_RADIUS: u32 = 3;
 
-        let is_correct_type = {
-            let screen: &dyn Screen = &**screen_box;
+pub struct Button {
+    //placed inside the button's bounds when it's drawn
+    label: Label,
+    confirm_label: Label,
+    bounds: Rectangle,
+    //set by positioned, resolved again against the panel on every draw
+    layout: Option<(Position, Extent)>,
+    panel: Cell<(u32, u32)>,
+    pressed: bool,
+    focused: bool,
+    confirm_window: Option<Duration>,
+    armed_at: Option<Instant>,
+    on_click: Option<Box<dyn Fn() + Send>>,
+}
 
-            screen.type_id() == std::any::TypeId::of::<T>()
-        };
+impl Button {
+    //see Label::positioned
+    pub fn positioned(text: &str, position: Position, extent: Extent, display: &dyn Canvas) -> Self {
+        let (x, y) = position.resolve(display.width(), display.height());
+        let (width, height) = extent.resolve(display.width(), display.height());
+        let mut button = Self::new(text, x, y, width, height);
+        button.layout = Some((position, extent));
+        button.panel.set((display.width(), display.height()));
+        button
+    }
 
-        if is_correct_type {
-            unsafe {
-                let ptr = &mut **screen_box as *mut dyn Screen;
-                Some(&mut *(ptr as *mut T))
-            }
-        } else {
-            None
-        }
+    pub fn new(text: &str, x: i32, y: i32, width: u32, height: u32) -> Self {
+        Self {
+            label: Label::new(text, 0, 0, TextSize::Normal).with_alignment(Alignment::Center, width),
+            confirm_label: Label::new("Confirm?", 0, 0, TextSize::Normal).with_alignment(Alignment::Center, width),
+            bounds: Rectangle { x, y, width, height },
+            layout: None,
+            panel: Cell::new((0, 0)),
+            pressed: false,
+            focused: false,
+            confirm_window: None,
+            armed_at: None,
+            on_click: None,
+        }
+    }
+
+    /// For destructive actions: the first click only asks "Confirm?", a second click
+    /// within `DEFAULT_CONFIRM_WINDOW` runs `on_click`.
+    pub fn require_confirm(self) -> Self {
+        self.with_confirm_window(DEFAULT_CONFIRM_WINDOW)
+    }
+
+    pub fn with_confirm_window(mut self, window: Duration) -> Self {
+        self.confirm_window = Some(window);
+        self
+    }
+
+    pub fn set_on_click<F>(&mut self, callback: F)
+    where
+        F: Fn() + Send + 'static,
+    {
+        self.on_click = Some(Box::new(callback));
+    }
+
+    //checked against the clock on every draw, so an expired prompt never shows even without an event to clear it
+    pub fn is_awaiting_confirm(&self) -> bool {
+        match (self.armed_at, self.confirm_window) {
+            (Some(armed_at), Some(window)) => armed_at.elapsed() < window,
+            _ => false,
+        }
+    }
+
+    fn click(&mut self) {
+        if self.confirm_window.is_some() && !self.is_awaiting_confirm() {
+            self.armed_at = Some(Instant::now());
+            return;
+        }
+
+        self.armed_at = None;
+        if let Some(callback) = &self.on_click {
+            callback();
+        }
+    }
+
+    //the bounds on the panel size last drawn to
+    fn current_bounds(&self) -> Rectangle {
+        match self.layout {
+            Some((position, extent)) => {
+                let (panel_width, panel_height) = self.panel.get();
+                let (x, y) = position.resolve(panel_width, panel_height);
+                let (width, height) = extent.resolve(panel_width, panel_height);
+                Rectangle { x, y, width, height }
+            },
+            None => self.bounds.clone(),
+        }
+    }
+}
+
+impl Widget for Button {
+    fn draw(&self, display: &dyn Canvas) -> Result<(), DisplayError> {
+        if self.layout.is_some() {
+            self.panel.set((display.width(), display.height()));
+        }
+        let bounds = self.current_bounds();
+
+        if self.focused {
+            draw_focus_ring(display, &bounds)?;
+        }
+
+        display.draw_rounded_rectangle(
+            bounds.x,
+            bounds.y,
+            bounds.width,
+            bounds.height,
+            BUTTON_CORNER_RADIUS,
+            self.pressed,
+        )?;
+
+        let label = if self.is_awaiting_confirm() { &self.confirm_label } else { &self.label };
+        let label_y = bounds.y + (bounds.height as i32 - label.get_bounds().height as i32) / 2;
+        label.draw_at(display, Point::new(bounds.x, label_y), Some(bounds.width))
+    }
+
+    fn handle_event(&mut self, event: &Event) -> bool {
+        match event {
+            Event::Select => {
+                self.set_pressed(true);
+                true
+            },
+            //releases aren't translated, whichever one follows a Select completes the click
+            Event::ButtonReleased(_) if self.pressed => {
+                self.set_pressed(false);
+                self.click();
+                true
+            },
+            //claiming the tick once the prompt has run out gets the plain label redrawn
+            Event::Timer(_) | Event::SystemTick if self.armed_at.is_some() && !self.is_awaiting_confirm() => {
+                self.armed_at = None;
+                true
+            },
+            _ => false,
+        }
+    }
+
+    fn get_bounds(&self) -> Rectangle {
+        self.current_bounds()
+    }
+
+    //pins the button in pixels, at the size it has now
+    fn set_position(&mut self, x: i32, y: i32) {
+        self.bounds = Rectangle { x, y, ..self.current_bounds() };
+        self.layout = None;
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    //moving focus elsewhere backs out of a pending confirm
+    fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+        if !focused {
+            self.reset();
+        }
+    }
+
+    //the frame fills while pressed, so the label flips to stay readable on it
+    fn set_pressed(&mut self, pressed: bool) {
+        self.pressed = pressed;
+        self.label.set_inverted(pressed);
+        self.confirm_label.set_inverted(pressed);
+    }
+
+    fn reset(&mut self) {
+        self.set_pressed(false);
+        self.armed_at = None;
+    }
+}
+
+pub struct Checkbox {
+    label: Label,
+    bounds: Rectangle,
+    checked: bool,
+    focused: bool,
+    pressed: bool,
+    on_change: Option<Box<dyn Fn(bool) + Send>>,
+}
+
+impl Checkbox {
+    pub fn new(text: &str, x: i32, y: i32, checked: bool) -> Self {
+        let label = Label::new(text, x + 12, y, TextSize::Normal);
+        let width = 12 + label.get_bounds().width;
+
+        Self {
+            label,
+            bounds: Rectangle { x, y, width, height: 10 },
+            checked,
+            focused: false,
+            pressed: false,
+            on_change: None,
+        }
+    }
+
+    pub fn is_checked(&self) -> bool {
+        self.checked
+    }
+
+    pub fn set_checked(&mut self, checked: bool) {
+        self.checked = checked;
+    }
+
+    pub fn set_on_change<F>(&mut self, callback: F)
+    where
+        F: Fn(bool) + Send + 'static,
+    {
+        self.on_change = Some(Box::new(callback));
+    }
+}
+
+impl Widget for Checkbox {
+    fn draw(&self, display: &dyn Canvas) -> Result<(), DisplayError> {
+        if self.focused {
+            draw_focus_ring(display, &self.bounds)?;
+        }
+        if self.pressed {
+            draw_press_ring(display, &self.bounds)?;
+        }
+
+        display.draw_rectangle(self.bounds.x, self.bounds.y + 1, 8, 8, false)?;
+
+        if self.checked {
+            display.draw_rectangle(self.bounds.x + 2, self.bounds.y + 3, 4, 4, true)?;
+        }
+
+        self.label.draw(display)
+    }
+
+    fn handle_event(&mut self, event: &Event) -> bool {
+        match event {
+            Event::Select => {
+                self.checked = !self.checked;
+                if let Some(callback) = &self.on_change {
+                    callback(self.checked);
+                }
+                true
+            },
+            _ => false,
+        }
+    }
+
+    fn get_bounds(&self) -> Rectangle {
+        self.bounds.clone()
+    }
+
+    fn set_position(&mut self, x: i32, y: i32) {
+        //label keeps its offset inside the widget
+        let label_bounds = self.label.get_bounds();
+        self.label.set_position(label_bounds.x + x - self.bounds.x, label_bounds.y + y - self.bounds.y);
+
+        self.bounds.x = x;
+        self.bounds.y = y;
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    fn set_pressed(&mut self, pressed: bool) {
+        self.pressed = pressed;
+    }
+}
+
+pub struct ToggleSwitch {
+    label: Label,
+    bounds: Rectangle,
+    on: bool,
+    focused: bool,
+    pressed: bool,
+    on_change: Option<Box<dyn Fn(bool) + Send>>,
+}
+
+impl ToggleSwitch {
+    pub fn new(text: &str, x: i32, y: i32, on: bool) -> Self {
+        let label = Label::new(text, x + 20, y, TextSize::Normal);
+        let width = 20 + label.get_bounds().width;
+
+        Self {
+            label,
+            bounds: Rectangle { x, y, width, height: 10 },
+            on,
+            focused: false,
+            pressed: false,
+            on_change: None,
+        }
+    }
+
+    pub fn is_on(&self) -> bool {
+        self.on
+    }
+
+    pub fn set_on(&mut self, on: bool) {
+        self.on = on;
+    }
+
+    pub fn set_on_change<F>(&mut self, callback: F)
+    where
+        F: Fn(bool) + Send + 'static,
+    {
+        self.on_change = Some(Box::new(callback));
+    }
+}
+
+impl Widget for ToggleSwitch {
+    fn draw(&self, display: &dyn Canvas) -> Result<(), DisplayError> {
+        if self.focused {
+            draw_focus_ring(display, &self.bounds)?;
+        }
+        if self.pressed {
+            draw_press_ring(display, &self.bounds)?;
+        }
+
+        display.draw_rectangle(self.bounds.x, self.bounds.y + 1, 16, 8, false)?;
+
+        //knob sits left when off, right when on
+        let knob_x = if self.on { self.bounds.x + 9 } else { self.bounds.x + 1 };
+        display.draw_rectangle(knob_x, self.bounds.y + 2, 6, 6, true)?;
+
+        self.label.draw(display)
+    }
+
+    fn handle_event(&mut self, event: &Event) -> bool {
+        match event {
+            Event::Select => {
+                self.on = !self.on;
+                if let Some(callback) = &self.on_change {
+                    callback(self.on);
+                }
+                true
+            },
+            _ => false,
+        }
+    }
+
+    fn get_bounds(&self) -> Rectangle {
+        self.bounds.clone()
+    }
+
+    fn set_position(&mut self, x: i32, y: i32) {
+        //label keeps its offset inside the widget
+        let label_bounds = self.label.get_bounds();
+        self.label.set_position(label_bounds.x + x - self.bounds.x, label_bounds.y + y - self.bounds.y);
+
+        self.bounds.x = x;
+        self.bounds.y = y;
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    fn set_pressed(&mut self, pressed: bool) {
+        self.pressed = pressed;
+    }
+}
+
+pub struct Slider {
+    bounds: Rectangle,
+    value: u8,
+    step: u8,
+    focused: bool,
+    pressed: bool,
+    on_change: Option<Box<dyn Fn(u8) + Send>>,
+}
+
+impl Slider {
+    pub fn new(x: i32, y: i32, width: u32, value: u8) -> Self {
+        Self {
+            bounds: Rectangle { x, y, width, height: 8 },
+            value: value.min(100),
+            step: 5,
+            focused: false,
+            pressed: false,
+            on_change: None,
+        }
+    }
+
+    pub fn with_step(mut self, step: u8) -> Self {
+        self.step = step.max(1);
+        self
+    }
+
+    pub fn get_value(&self) -> u8 {
+        self.value
+    }
+
+    pub fn set_value(&mut self, value: u8) {
+        self.value = value.min(100);
+    }
+
+    pub fn set_on_change<F>(&mut self, callback: F)
+    where
+        F: Fn(u8) + Send + 'static,
+    {
+        self.on_change = Some(Box::new(callback));
+    }
+
+    fn adjust(&mut self, increase: bool) -> bool {
+        let new_value = if increase {
+            self.value.saturating_add(self.step).min(100)
+        } else {
+            self.value.saturating_sub(self.step)
+        };
+
+        //returning false at either end stops the screen redrawing for nothing
+        if new_value == self.value {
+            return false;
+        }
+
+        self.value = new_value;
+        if let Some(callback) = &self.on_change {
+            callback(self.value);
+        }
+        true
+    }
+}
+
+impl Widget for Slider {
+    fn draw(&self, display: &dyn Canvas) -> Result<(), DisplayError> {
+        if self.focused {
+            draw_focus_ring(display, &self.bounds)?;
+        }
+        if self.pressed {
+            draw_press_ring(display, &self.bounds)?;
+        }
+
+        let knob_width = 4u32;
+        let travel = self.bounds.width.saturating_sub(knob_width);
+        let knob_x = self.bounds.x + (travel * self.value as u32 / 100) as i32;
+
+        display.draw_rectangle(self.bounds.x, self.bounds.y + 2, self.bounds.width, 4, false)?;
+        display.draw_rectangle(knob_x, self.bounds.y, knob_width, self.bounds.height, true)
+    }
+
+    fn handle_event(&mut self, event: &Event) -> bool {
+        match event {
+            Event::Down => self.adjust(true),
+            Event::Up => self.adjust(false),
+            Event::EncoderRotated(_, direction) => self.adjust(*direction > 0),
+            _ => false,
+        }
+    }
+
+    fn get_bounds(&self) -> Rectangle {
+        self.bounds.clone()
+    }
+
+    fn set_position(&mut self, x: i32, y: i32) {
+        self.bounds.x = x;
+        self.bounds.y = y;
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    fn set_pressed(&mut self, pressed: bool) {
+        self.pressed = pressed;
+    }
+}
+
+const STEPPER_ARROW_HEIGHT: u32 = 3;
+
+//a number picked with the scroll button, e.g. brightness or an alarm hour
+pub struct Stepper {
+    bounds: Rectangle,
+    value: i32,
+    min: i32,
+    max: i32,
+    step: i32,
+    wrap: bool,
+    focused: bool,
+    pressed: bool,
+    on_change: Option<Box<dyn Fn(i32) + Send>>,
+}
+
+impl Stepper {
+    pub fn new(x: i32, y: i32, width: u32, min: i32, max: i32, value: i32) -> Self {
+        let (min, max) = if min <= max { (min, max) } else { (max, min) };
+
+        Self {
+            bounds: Rectangle { x, y, width, height: font_metrics(TextSize::Normal).1 + 2 * (STEPPER_ARROW_HEIGHT + 1) },
+            value: value.clamp(min, max),
+            min,
+            max,
+            step: 1,
+            wrap: false,
+            focused: false,
+            pressed: false,
+            on_change: None,
+        }
+    }
+
+    pub fn with_step(mut self, step: i32) -> Self {
+        self.step = step.max(1);
+        self
+    }
+
+    //stepping past either end comes round to the other instead of stopping
+    pub fn with_wrap(mut self, wrap: bool) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    pub fn value(&self) -> i32 {
+        self.value
+    }
+
+    pub fn set_value(&mut self, value: i32) {
+        self.value = value.clamp(self.min, self.max);
+    }
+
+    pub fn set_on_change<F>(&mut self, callback: F)
+    where
+        F: Fn(i32) + Send + 'static,
+    {
+        self.on_change = Some(Box::new(callback));
+    }
+
+    fn can_increase(&self) -> bool {
+        self.wrap || self.value < self.max
+    }
+
+    fn can_decrease(&self) -> bool {
+        self.wrap || self.value > self.min
+    }
+
+    fn adjust(&mut self, increase: bool) -> bool {
+        let new_value = if increase {
+            match self.value.saturating_add(self.step) {
+                v if v > self.max && self.wrap => self.min,
+                v => v.min(self.max),
+            }
+        } else {
+            match self.value.saturating_sub(self.step) {
+                v if v < self.min && self.wrap => self.max,
+                v => v.max(self.min),
+            }
+        };
+
+        //same as Slider, nothing to redraw when already pinned at a bound
+        if new_value == self.value {
+            return false;
+        }
+
+        self.value = new_value;
+        if let Some(callback) = &self.on_change {
+            callback(self.value);
+        }
+        true
+    }
+
+    fn draw_arrow(&self, display: &dyn Canvas, tip_y: i32, pointing_up: bool) -> Result<(), DisplayError> {
+        let cx = self.bounds.x + self.bounds.width as i32 / 2;
+
+        for row in 0..STEPPER_ARROW_HEIGHT as i32 {
+            let y = if pointing_up { tip_y + row } else { tip_y - row };
+            display.draw_hline(cx - row, y, (2 * row + 1) as u32)?;
+        }
+        Ok(())
+    }
+}
+
+impl Widget for Stepper {
+    fn draw(&self, display: &dyn Canvas) -> Result<(), DisplayError> {
+        if self.focused {
+            draw_focus_ring(display, &self.bounds)?;
+        }
+        if self.pressed {
+            draw_press_ring(display, &self.bounds)?;
+        }
+
+        //arrows only show for directions that still go somewhere
+        if self.can_increase() {
+            self.draw_arrow(display, self.bounds.y, true)?;
+        }
+
+        let text = self.value.to_string();
+        let text_x = self.bounds.x + (self.bounds.width as i32 - text_width(&text, TextSize::Normal) as i32) / 2;
+        let text_y = self.bounds.y + STEPPER_ARROW_HEIGHT as i32 + 1;
+        display.draw_text(&text, text_x, text_y, TextSize::Normal)?;
+
+        if self.can_decrease() {
+            self.draw_arrow(display, self.bounds.y + self.bounds.height as i32 - 1, false)?;
+        }
+
+        Ok(())
+    }
+
+    fn handle_event(&mut self, event: &Event) -> bool {
+        match event {
+            Event::Down => self.adjust(true),
+            Event::Up => self.adjust(false),
+            Event::EncoderRotated(_, direction) => self.adjust(*direction > 0),
+            _ => false,
+        }
+    }
+
+    fn get_bounds(&self) -> Rectangle {
+        self.bounds.clone()
+    }
+
+    fn set_position(&mut self, x: i32, y: i32) {
+        self.bounds.x = x;
+        self.bounds.y = y;
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    fn set_pressed(&mut self, pressed: bool) {
+        self.pressed = pressed;
+    }
+}
+
+pub struct WifiIcon {
+    bounds: Rectangle,
+    connected: bool,
+    bars: u8,
+}
+
+impl WifiIcon {
+    pub fn new(x: i32, y: i32) -> Self {
+        Self {
+            bounds: Rectangle { x, y, width: 11, height: 8 },
+            connected: false,
+            bars: 0,
+        }
+    }
+
+    pub fn bars_for_rssi(rssi: i8) -> u8 {
+        match rssi {
+            r if r > -55 => 4,
+            r if r > -67 => 3,
+            r if r > -75 => 2,
+            r if r > -85 => 1,
+            _ => 0,
+        }
+    }
+
+    //both setters report whether the icon actually looks different, so callers can skip redraws
+    pub fn set_rssi(&mut self, rssi: i8) -> bool {
+        let bars = Self::bars_for_rssi(rssi);
+        let changed = bars != self.bars || !self.connected;
+
+        self.bars = bars;
+        self.connected = true;
+        changed
+    }
+
+    pub fn set_connected(&mut self, connected: bool) -> bool {
+        let changed = connected != self.connected;
+
+        self.connected = connected;
+        if !connected {
+            self.bars = 0;
+        }
+        changed
+    }
+}
+
+impl Widget for WifiIcon {
+    fn draw(&self, display: &dyn Canvas) -> Result<(), DisplayError> {
+        let x = self.bounds.x;
+        let y = self.bounds.y;
+
+        if !self.connected {
+            display.draw_line(x + 2, y, x + 8, y + 6)?;
+            return display.draw_line(x + 2, y + 6, x + 8, y);
+        }
+
+        for bar in 0..4u8 {
+            let bar_x = x + bar as i32 * 3;
+            let bar_height = 2 + bar as u32 * 2;
+            let bar_y = y + self.bounds.height as i32 - bar_height as i32;
+
+            if bar < self.bars {
+                display.draw_rectangle(bar_x, bar_y, 2, bar_height, true)?;
+            } else {
+                //unlit bars are just a stub on the baseline
+                display.draw_rectangle(bar_x, y + self.bounds.height as i32 - 1, 2, 1, true)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle_event(&mut self, event: &Event) -> bool {
+        match event {
+            Event::NetworkUp => self.set_connected(true),
+            Event::NetworkDown => self.set_connected(false),
+            Event::NetworkSignal(rssi) => self.set_rssi(*rssi),
+            _ => false,
+        }
+    }
+
+    fn get_bounds(&self) -> Rectangle {
+        self.bounds.clone()
+    }
+
+    fn set_position(&mut self, x: i32, y: i32) {
+        self.bounds.x = x;
+        self.bounds.y = y;
+    }
+}
+
+pub const STATUS_BAR_HEIGHT: u32 = 12;
+
+//top strip with a title, uptime clock and battery/wifi icons, fed entirely by events
+pub struct StatusBar {
+    title: Label,
+    clock: Label,
+    wifi: WifiIcon,
+    battery: Option<u8>,
+    system_clock: Arc<SystemClock>,
+    bounds: Rectangle,
+}
+
+impl StatusBar {
+    pub fn new(title: &str, width: u32, system_clock: Arc<SystemClock>) -> Self {
+        let width = width as i32;
+
+        Self {
+            title: Label::new(title, 2, 2, TextSize::Small),
+            clock: Label::new("00:00", width - 58, 2, TextSize::Small),
+            wifi: WifiIcon::new(width - 12, 1),
+            battery: None,
+            system_clock,
+            bounds: Rectangle { x: 0, y: 0, width: width as u32, height: STATUS_BAR_HEIGHT },
+        }
+    }
+
+    pub fn set_title(&mut self, title: &str) {
+        self.title.set_text(title);
+    }
+
+    pub fn set_battery(&mut self, percentage: Option<u8>) -> bool {
+        let percentage = percentage.map(|p| p.min(100));
+        let changed = percentage != self.battery;
+
+        self.battery = percentage;
+        changed
+    }
+
+    //real time once sntp has synced, uptime as hh:mm until then
+    pub fn update_clock(&mut self) -> bool {
+        let text = match self.system_clock.wall_clock() {
+            Some(time) => format!("{:02}:{:02}", time.hour, time.minute),
+            None => {
+                let minutes = self.system_clock.uptime().as_secs() / 60;
+                format!("{:02}:{:02}", (minutes / 60) % 100, minutes % 60)
+            },
+        };
+
+        if text == self.clock.text {
+            return false;
+        }
+
+        self.clock.set_text(&text);
+        true
+    }
+
+    fn draw_battery(&self, display: &dyn Canvas, percentage: u8) -> Result<(), DisplayError> {
+        let x = self.bounds.x + self.bounds.width as i32 - 28;
+        let y = self.bounds.y + 2;
+
+        display.draw_rectangle(x, y, 12, 7, false)?;
+        display.draw_rectangle(x + 12, y + 2, 1, 3, true)?;
+
+        let fill = 10 * percentage as u32 / 100;
+        if fill > 0 {
+            display.draw_rectangle(x + 1, y + 1, fill, 5, true)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Widget for StatusBar {
+    fn draw(&self, display: &dyn Canvas) -> Result<(), DisplayError> {
+        //owns its strip outright so it can be redrawn without touching the rest of the screen
+        display.clear_region(self.bounds.x, self.bounds.y, self.bounds.width, self.bounds.height)?;
+
+        self.title.draw(display)?;
+        self.clock.draw(display)?;
+
+        if let Some(percentage) = self.battery {
+            self.draw_battery(display, percentage)?;
+        }
+
+        self.wifi.draw(display)?;
+
+        let bottom = self.bounds.y + self.bounds.height as i32 - 1;
+        display.draw_line(self.bounds.x, bottom, self.bounds.x + self.bounds.width as i32 - 1, bottom)
+    }
+
+    fn handle_event(&mut self, event: &Event) -> bool {
+        match event {
+            Event::NetworkUp | Event::NetworkDown | Event::NetworkSignal(_) => self.wifi.handle_event(event),
+            Event::BatteryLevel(percentage) | Event::BatteryLow(percentage) => self.set_battery(Some(*percentage)),
+            Event::Timer(_) | Event::SystemTick | Event::TimeSynced => self.update_clock(),
+            _ => false,
+        }
+    }
+
+    fn get_bounds(&self) -> Rectangle {
+        self.bounds.clone()
+    }
+
+    fn set_position(&mut self, x: i32, y: i32) {
+        let dx = x - self.bounds.x;
+        let dy = y - self.bounds.y;
+
+        for widget in [&mut self.title as &mut dyn Widget, &mut self.clock, &mut self.wifi] {
+            let bounds = widget.get_bounds();
+            widget.set_position(bounds.x + dx, bounds.y + dy);
+        }
+
+        self.bounds.x = x;
+        self.bounds.y = y;
+    }
+}
+
+pub struct ProgressBar {
+    bounds: Rectangle,
+    progress: u8,
+}
+
+impl ProgressBar {
+    pub fn new(x: i32, y: i32, width: u32, progress: u8) -> Self {
+        Self {
+            bounds: Rectangle { x, y, width, height: 8 },
+            progress: progress.min(100),
+        }
+    }
+
+    pub fn set_progress(&mut self, progress: u8) {
+        self.progress = progress.min(100);
+    }
+}
+
+impl Widget for ProgressBar {
+    fn draw(&self, display: &dyn Canvas) -> Result<(), DisplayError> {
+        display.draw_progress_bar(
+            self.bounds.x,
+            self.bounds.y,
+            self.bounds.width,
+            self.progress
+        )
+    }
+
+    fn handle_event(&mut self, _event: &Event) -> bool {
+        false
+    }
+
+    fn get_bounds(&self) -> Rectangle {
+        self.bounds.clone()
+    }
+
+    fn set_position(&mut self, x: i32, y: i32) {
+        self.bounds.x = x;
+        self.bounds.y = y;
+    }
+}
+
+pub struct Separator {
+    bounds: Rectangle,
+    orientation: Orientation,
+}
+
+impl Separator {
+    pub fn new(orientation: Orientation, x: i32, y: i32, length: u32) -> Self {
+        let (width, height) = match orientation {
+            Orientation::Horizontal => (length, 1),
+            Orientation::Vertical => (1, length),
+        };
+
+        Self {
+            bounds: Rectangle { x, y, width, height },
+            orientation,
+        }
+    }
+
+    pub fn horizontal(x: i32, y: i32, length: u32) -> Self {
+        Self::new(Orientation::Horizontal, x, y, length)
+    }
+
+    pub fn vertical(x: i32, y: i32, length: u32) -> Self {
+        Self::new(Orientation::Vertical, x, y, length)
+    }
+}
+
+impl Widget for Separator {
+    fn draw(&self, display: &dyn Canvas) -> Result<(), DisplayError> {
+        match self.orientation {
+            Orientation::Horizontal => display.draw_hline(self.bounds.x, self.bounds.y, self.bounds.width),
+            Orientation::Vertical => display.draw_vline(self.bounds.x, self.bounds.y, self.bounds.height),
+        }
+    }
+
+    fn handle_event(&mut self, _event: &Event) -> bool {
+        false
+    }
+
+    fn get_bounds(&self) -> Rectangle {
+        self.bounds.clone()
+    }
+
+    fn set_position(&mut self, x: i32, y: i32) {
+        self.bounds.x = x;
+        self.bounds.y = y;
+    }
+}
+
+const GAUGE_SEGMENTS: usize = 24;
+
+pub struct Gauge {
+    bounds: Rectangle,
+    progress: u8,
+    points: Vec<(i32, i32)>,
+}
+
+impl Gauge {
+    pub fn new(cx: i32, cy: i32, radius: u32, progress: u8) -> Self {
+        //270 degree sweep, clockwise from bottom-left to bottom-right, worked out once up front
+        let start = 135.0f32.to_radians();
+        let sweep = 270.0f32.to_radians();
+
+        let points = (0..=GAUGE_SEGMENTS)
+            .map(|i| {
+                let angle = start + sweep * i as f32 / GAUGE_SEGMENTS as f32;
+                let x = cx + (radius as f32 * angle.cos()).round() as i32;
+                let y = cy + (radius as f32 * angle.sin()).round() as i32;
+                (x, y)
+            })
+            .collect();
+
+        Self {
+            bounds: Rectangle {
+                x: cx - radius as i32,
+                y: cy - radius as i32,
+                width: radius * 2 + 1,
+                height: radius * 2 + 1,
+            },
+            progress: progress.min(100),
+            points,
+        }
+    }
+
+    pub fn set_progress(&mut self, progress: u8) {
+        self.progress = progress.min(100);
+    }
+}
+
+impl Widget for Gauge {
+    fn draw(&self, display: &dyn Canvas) -> Result<(), DisplayError> {
+        let (first, last) = (self.points[0], self.points[GAUGE_SEGMENTS]);
+        let filled = self.progress as usize * GAUGE_SEGMENTS / 100;
+
+        //end stops mark the full range even when nothing is filled yet
+        display.draw_rectangle(first.0 - 1, first.1 - 1, 3, 3, true)?;
+        display.draw_rectangle(last.0 - 1, last.1 - 1, 3, 3, true)?;
+
+        for segment in self.points.windows(2).take(filled) {
+            display.draw_line(segment[0].0, segment[0].1, segment[1].0, segment[1].1)?;
+        }
+
+        let text = format!("{}%", self.progress);
+        let (_, line_height) = font_metrics(TextSize::Normal);
+        let text_x = self.bounds.x + (self.bounds.width as i32 - text_width(&text, TextSize::Normal) as i32) / 2;
+        let text_y = self.bounds.y + (self.bounds.height as i32 - line_height as i32) / 2;
+        display.draw_text(&text, text_x, text_y, TextSize::Normal)
+    }
+
+    fn handle_event(&mut self, _event: &Event) -> bool {
+        false
+    }
+
+    fn get_bounds(&self) -> Rectangle {
+        self.bounds.clone()
+    }
+
+    fn set_position(&mut self, x: i32, y: i32) {
+        let dx = x - self.bounds.x;
+        let dy = y - self.bounds.y;
+
+        for point in &mut self.points {
+            point.0 += dx;
+            point.1 += dy;
+        }
+
+        self.bounds.x = x;
+        self.bounds.y = y;
+    }
+}
+
+const SPINNER_STEPS: usize = 12;
+//how much of the circle the moving segment covers
+const SPINNER_ARC: usize = 3;
+
+//for waits with no measurable progress, e.g. "Connecting..."
+pub struct Spinner {
+    bounds: Rectangle,
+    points: Vec<(i32, i32)>,
+    //in revolutions, only the fractional part matters
+    angle: f32,
+    speed: f32,
+    last_tick: Instant,
+}
+
+impl Spinner {
+    pub fn new(cx: i32, cy: i32, radius: u32) -> Self {
+        //positions round the circle worked out once, drawing is then just a few lines
+        let points = (0..SPINNER_STEPS)
+            .map(|i| {
+                let angle = std::f32::consts::TAU * i as f32 / SPINNER_STEPS as f32;
+                let x = cx + (radius as f32 * angle.cos()).round() as i32;
+                let y = cy + (radius as f32 * angle.sin()).round() as i32;
+                (x, y)
+            })
+            .collect();
+
+        Self {
+            bounds: Rectangle {
+                x: cx - radius as i32,
+                y: cy - radius as i32,
+                width: radius * 2 + 1,
+                height: radius * 2 + 1,
+            },
+            points,
+            angle: 0.0,
+            speed: 1.0,
+            last_tick: Instant::now(),
+        }
+    }
+
+    //revolutions per second
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed.max(0.0);
+        self
+    }
+
+    fn step(&self) -> usize {
+        (self.angle * SPINNER_STEPS as f32) as usize % SPINNER_STEPS
+    }
+
+    //returns true when the segment moved to a new position and the spinner should be redrawn
+    pub fn update(&mut self, delta: Duration) -> bool {
+        let before = self.step();
+        self.angle = (self.angle + self.speed * delta.as_secs_f32()).fract();
+        self.step() != before
+    }
+}
+
+impl Widget for Spinner {
+    fn draw(&self, display: &dyn Canvas) -> Result<(), DisplayError> {
+        let head = self.step();
+
+        for offset in 0..SPINNER_ARC {
+            let from = self.points[(head + offset) % SPINNER_STEPS];
+            let to = self.points[(head + offset + 1) % SPINNER_STEPS];
+            display.draw_line(from.0, from.1, to.0, to.1)?;
+        }
+
+        Ok(())
+    }
+
+    fn handle_event(&mut self, event: &Event) -> bool {
+        match event {
+            Event::SystemTick => {
+                let now = Instant::now();
+                let delta = now.duration_since(self.last_tick);
+                self.last_tick = now;
+                self.update(delta)
+            },
+            _ => false,
+        }
+    }
+
+    fn get_bounds(&self) -> Rectangle {
+        self.bounds.clone()
+    }
+
+    fn set_position(&mut self, x: i32, y: i32) {
+        let dx = x - self.bounds.x;
+        let dy = y - self.bounds.y;
+
+        for point in &mut self.points {
+            point.0 += dx;
+            point.1 += dy;
+        }
+
+        self.bounds.x = x;
+        self.bounds.y = y;
+    }
+}
+
+pub struct ScrollView {
+    bounds: Rectangle,
+    items: Vec<String>,
+    size: TextSize,
+    row_height: u32,
+    offset: i32,
+    selected: usize,
+    focused: bool,
+    pressed: bool,
+}
+
+impl ScrollView {
+    pub fn new(x: i32, y: i32, width: u32, height: u32, size: TextSize) -> Self {
+        //a couple of pixels of breathing room so the selection box doesn't touch the text
+        let row_height = font_metrics(size).1 + 2;
+
+        Self {
+            bounds: Rectangle { x, y, width, height },
+            items: Vec::new(),
+            size,
+            row_height,
+            offset: 0,
+            selected: 0,
+            focused: false,
+            pressed: false,
+        }
+    }
+
+    pub fn add_item(&mut self, text: &str) {
+        self.items.push(text.to_string());
+    }
+
+    pub fn selected_index(&self) -> usize {
+        self.selected
+    }
+
+    pub fn scroll_by(&mut self, delta: i32) {
+        let content_height = (self.items.len() as u32 * self.row_height) as i32;
+        let max_offset = (content_height - self.bounds.height as i32).max(0);
+
+        self.offset = (self.offset + delta).clamp(0, max_offset);
+    }
+
+    pub fn select(&mut self, index: usize) {
+        if index < self.items.len() {
+            self.selected = index;
+            self.ensure_visible();
+        }
+    }
+
+    pub fn select_next(&mut self) {
+        if !self.items.is_empty() {
+            self.select((self.selected + 1) % self.items.len());
+        }
+    }
+
+    pub fn select_prev(&mut self) {
+        if !self.items.is_empty() {
+            let index = if self.selected == 0 { self.items.len() - 1 } else { self.selected - 1 };
+            self.select(index);
+        }
+    }
+
+    fn ensure_visible(&mut self) {
+        let row_top = (self.selected as u32 * self.row_height) as i32;
+        let row_bottom = row_top + self.row_height as i32;
+
+        if row_top < self.offset {
+            self.scroll_by(row_top - self.offset);
+        } else if row_bottom > self.offset + self.bounds.height as i32 {
+            self.scroll_by(row_bottom - self.offset - self.bounds.height as i32);
+        }
+    }
+}
+
+impl Widget for ScrollView {
+    fn draw(&self, display: &dyn Canvas) -> Result<(), DisplayError> {
+        if self.focused {
+            draw_focus_ring(display, &self.bounds)?;
+        }
+        if self.pressed {
+            draw_press_ring(display, &self.bounds)?;
+        }
+
+        let view_top = self.bounds.y;
+        let view_bottom = self.bounds.y + self.bounds.height as i32;
+
+        for (index, item) in self.items.iter().enumerate() {
+            let row_y = view_top + (index as u32 * self.row_height) as i32 - self.offset;
+
+            //only draw rows that fit entirely inside the viewport
+            if row_y < view_top || row_y + self.row_height as i32 > view_bottom {
+                continue;
+            }
+
+            if index == self.selected {
+                display.draw_text_inverted(item, self.bounds.x + 2, row_y + 1, self.size)?;
+            } else {
+                display.draw_text(item, self.bounds.x + 2, row_y + 1, self.size)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle_event(&mut self, event: &Event) -> bool {
+        match event {
+            Event::Down => {
+                self.select_next();
+                true
+            },
+            _ => false,
+        }
+    }
+
+    fn get_bounds(&self) -> Rectangle {
+        self.bounds.clone()
+    }
+
+    fn set_position(&mut self, x: i32, y: i32) {
+        self.bounds.x = x;
+        self.bounds.y = y;
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    fn set_pressed(&mut self, pressed: bool) {
+        self.pressed = pressed;
+    }
+}
+
+//the item and whether it's the selected row
+type RowRenderer<T> = Box<dyn Fn(&T, bool) -> String + Send>;
+
+//like ScrollView but over any data, only the rows in the window are ever rendered to strings
+pub struct ListView<T> {
+    bounds: Rectangle,
+    items: Vec<T>,
+    render_row: RowRenderer<T>,
+    on_select: Option<Box<dyn Fn(usize) + Send>>,
+    size: TextSize,
+    row_height: u32,
+    first_visible: usize,
+    selected: usize,
+    focused: bool,
+    pressed: bool,
+}
+
+impl<T> ListView<T> {
+    pub fn new<F>(x: i32, y: i32, width: u32, height: u32, items: Vec<T>, render_row: F) -> Self
+    where
+        F: Fn(&T, bool) -> String + Send + 'static,
+    {
+        Self {
+            bounds: Rectangle { x, y, width, height },
+            items,
+            render_row: Box::new(render_row),
+            on_select: None,
+            size: TextSize::Small,
+            row_height: 10,
+            first_visible: 0,
+            selected: 0,
+            focused: false,
+            pressed: false,
+        }
+    }
+
+    pub fn set_on_select<F>(&mut self, callback: F)
+    where
+        F: Fn(usize) + Send + 'static,
+    {
+        self.on_select = Some(Box::new(callback));
+    }
+
+    //keeps the selection on the same index where it still exists
+    pub fn set_items(&mut self, items: Vec<T>) {
+        self.items = items;
+        self.selected = self.selected.min(self.items.len().saturating_sub(1));
+        self.ensure_visible();
+    }
+
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+
+    pub fn selected_index(&self) -> usize {
+        self.selected
+    }
+
+    pub fn selected_item(&self) -> Option<&T> {
+        self.items.get(self.selected)
+    }
+
+    fn visible_rows(&self) -> usize {
+        (self.bounds.height / self.row_height).max(1) as usize
+    }
+
+    pub fn select(&mut self, index: usize) {
+        if index < self.items.len() {
+            self.selected = index;
+            self.ensure_visible();
+        }
+    }
+
+    pub fn select_next(&mut self) {
+        if !self.items.is_empty() {
+            self.select((self.selected + 1) % self.items.len());
+        }
+    }
+
+    pub fn select_prev(&mut self) {
+        if !self.items.is_empty() {
+            let index = if self.selected == 0 { self.items.len() - 1 } else { self.selected - 1 };
+            self.select(index);
+        }
+    }
+
+    pub fn activate(&self) {
+        if let Some(callback) = &self.on_select {
+            if self.selected < self.items.len() {
+                callback(self.selected);
+            }
+        }
+    }
+
+    fn ensure_visible(&mut self) {
+        let rows = self.visible_rows();
+
+        if self.selected < self.first_visible {
+            self.first_visible = self.selected;
+        } else if self.selected >= self.first_visible + rows {
+            self.first_visible = self.selected + 1 - rows;
+        }
+    }
+}
+
+impl<T> Widget for ListView<T> {
+    fn draw(&self, display: &dyn Canvas) -> Result<(), DisplayError> {
+        if self.focused {
+            draw_focus_ring(display, &self.bounds)?;
+        }
+        if self.pressed {
+            draw_press_ring(display, &self.bounds)?;
+        }
+
+        let visible = self.items.iter().enumerate().skip(self.first_visible).take(self.visible_rows());
+
+        for (row, (index, item)) in visible.enumerate() {
+            let row_y = self.bounds.y + (row as u32 * self.row_height) as i32;
+            let selected = index == self.selected;
+            let text = (self.render_row)(item, selected);
+
+            if selected {
+                display.draw_text_inverted(&text, self.bounds.x + 2, row_y + 1, self.size)?;
+            } else {
+                display.draw_text_clipped(&text, self.bounds.x + 2, row_y + 1, self.size, self.bounds.x, self.bounds.width)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle_event(&mut self, event: &Event) -> bool {
+        match event {
+            Event::Down => {
+                self.select_next();
+                true
+            },
+            Event::Up => {
+                self.select_prev();
+                true
+            },
+            Event::EncoderRotated(_, direction) => {
+                if *direction > 0 {
+                    self.select_next();
+                } else {
+                    self.select_prev();
+                }
+                true
+            },
+            Event::Select => {
+                self.activate();
+                true
+            },
+            _ => false,
+        }
+    }
+
+    fn get_bounds(&self) -> Rectangle {
+        self.bounds.clone()
+    }
+
+    fn set_position(&mut self, x: i32, y: i32) {
+        self.bounds.x = x;
+        self.bounds.y = y;
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    fn set_pressed(&mut self, pressed: bool) {
+        self.pressed = pressed;
+    }
+}
+
+pub struct Dialog {
+    message: WrappedLabel,
+    options: Vec<String>,
+    bounds: Rectangle,
+    selected: usize,
+    closed: bool,
+    on_result: Option<Box<dyn FnOnce(usize) + Send>>,
+}
+
+impl Dialog {
+    pub fn new(message: &str, options: &[&str]) -> Self {
+        let bounds = Rectangle { x: 8, y: 10, width: 112, height: 44 };
+
+        Self {
+            message: WrappedLabel::new(message, bounds.x + 4, bounds.y + 4, bounds.width - 8, TextSize::Small),
+            options: options.iter().map(|option| option.to_string()).collect(),
+            bounds,
+            selected: 0,
+            closed: false,
+            on_result: None,
+        }
+    }
+
+    pub fn confirm<F>(message: &str, callback: F) -> Self
+    where
+        F: FnOnce(bool) + Send + 'static,
+    {
+        let mut dialog = Self::new(message, &["Yes", "No"]);
+        dialog.set_on_result(move |index| callback(index == 0));
+        dialog
+    }
+
+    pub fn set_on_result<F>(&mut self, callback: F)
+    where
+        F: FnOnce(usize) + Send + 'static,
+    {
+        self.on_result = Some(Box::new(callback));
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+}
+
+impl Widget for Dialog {
+    fn draw(&self, display: &dyn Canvas) -> Result<(), DisplayError> {
+        //blank just the dialog's own box so the screen underneath stays visible around it
+        display.clear_region(self.bounds.x, self.bounds.y, self.bounds.width, self.bounds.height)?;
+        display.draw_rectangle(self.bounds.x, self.bounds.y, self.bounds.width, self.bounds.height, false)?;
+
+        self.message.draw(display)?;
+
+        let option_y = self.bounds.y + self.bounds.height as i32 - 12;
+        let slot_width = self.bounds.width as i32 / self.options.len().max(1) as i32;
+
+        for (index, option) in self.options.iter().enumerate() {
+            let option_x = self.bounds.x + slot_width * index as i32 + (slot_width - text_width(option, TextSize::Normal) as i32) / 2;
+
+            if index == self.selected {
+                display.draw_text_inverted(option, option_x, option_y, TextSize::Normal)?;
+            } else {
+                display.draw_text(option, option_x, option_y, TextSize::Normal)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle_event(&mut self, event: &Event) -> bool {
+        if self.options.is_empty() {
+            return false;
+        }
+
+        let count = self.options.len();
+
+        match event {
+            Event::Down => {
+                self.selected = (self.selected + 1) % count;
+                true
+            },
+            Event::Up => {
+                self.selected = (self.selected + count - 1) % count;
+                true
+            },
+            Event::EncoderRotated(_, direction) => {
+                self.selected = if *direction > 0 {
+                    (self.selected + 1) % count
+                } else {
+                    (self.selected + count - 1) % count
+                };
+                true
+            },
+            Event::Select => {
+                self.closed = true;
+                if let Some(callback) = self.on_result.take() {
+                    callback(self.selected);
+                }
+                true
+            },
+            _ => false,
+        }
+    }
+
+    fn get_bounds(&self) -> Rectangle {
+        self.bounds.clone()
+    }
+
+    fn set_position(&mut self, x: i32, y: i32) {
+        let message_bounds = self.message.get_bounds();
+        self.message.set_position(message_bounds.x + x - self.bounds.x, message_bounds.y + y - self.bounds.y);
+
+        self.bounds.x = x;
+        self.bounds.y = y;
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Orientation {
+    Vertical,
+    Horizontal,
+}
+
+pub struct LayoutBox {
+    bounds: Rectangle,
+    orientation: Orientation,
+    spacing: u32,
+    padding: u32,
+    children: Vec<Box<dyn Widget + Send>>,
+    focused: Option<usize>,
+}
+
+impl LayoutBox {
+    pub fn new(orientation: Orientation, x: i32, y: i32) -> Self {
+        Self {
+            bounds: Rectangle { x, y, width: 0, height: 0 },
+            orientation,
+            spacing: 2,
+            padding: 0,
+            children: Vec::new(),
+            focused: None,
+        }
+    }
+
+    pub fn vbox(x: i32, y: i32) -> Self {
+        Self::new(Orientation::Vertical, x, y)
+    }
+
+    pub fn hbox(x: i32, y: i32) -> Self {
+        Self::new(Orientation::Horizontal, x, y)
+    }
+
+    pub fn with_spacing(mut self, spacing: u32) -> Self {
+        self.spacing = spacing;
+        self.relayout();
+        self
+    }
+
+    pub fn with_padding(mut self, padding: u32) -> Self {
+        self.padding = padding;
+        self.relayout();
+        self
+    }
+
+    pub fn add<W>(&mut self, widget: W)
+    where
+        W: Widget + Send + 'static,
+    {
+        self.children.push(Box::new(widget));
+        self.relayout();
+    }
+
+    fn focus_child(&mut self, index: Option<usize>) {
+        for (i, child) in self.children.iter_mut().enumerate() {
+            child.set_focused(Some(i) == index);
+        }
+        self.focused = index;
+    }
+
+    fn step_focus(&mut self, forward: bool) -> bool {
+        let Some(current) = self.focused else {
+            return false;
+        };
+
+        //a nested box walks its own children before this one moves on
+        let moved = if forward {
+            self.children[current].focus_next()
+        } else {
+            self.children[current].focus_prev()
+        };
+        if moved {
+            return true;
+        }
+
+        let next = if forward {
+            (current + 1..self.children.len()).find(|&i| self.children[i].is_focusable())
+        } else {
+            (0..current).rev().find(|&i| self.children[i].is_focusable())
+        };
+
+        match next {
+            Some(index) => {
+                self.focus_child(Some(index));
+                if !forward {
+                    focus_last_inside(self.children[index].as_mut());
+                }
+                true
+            },
+            None => false,
+        }
+    }
+
+    fn relayout(&mut self) {
+        let mut cursor_x = self.bounds.x + self.padding as i32;
+        let mut cursor_y = self.bounds.y + self.padding as i32;
+        let mut main_extent = 0u32;
+        let mut cross_extent = 0u32;
+        let count = self.children.len();
+
+        for (index, child) in self.children.iter_mut().enumerate() {
+            child.set_position(cursor_x, cursor_y);
+            let child_bounds = child.get_bounds();
+
+            let gap = if index + 1 < count { self.spacing } else { 0 };
+
+            match self.orientation {
+                Orientation::Vertical => {
+                    cursor_y += (child_bounds.height + gap) as i32;
+                    main_extent += child_bounds.height + gap;
+                    cross_extent = cross_extent.max(child_bounds.width);
+                },
+                Orientation::Horizontal => {
+                    cursor_x += (child_bounds.width + gap) as i32;
+                    main_extent += child_bounds.width + gap;
+                    cross_extent = cross_extent.max(child_bounds.height);
+                },
+            }
+        }
+
+        let (width, height) = match self.orientation {
+            Orientation::Vertical => (cross_extent, main_extent),
+            Orientation::Horizontal => (main_extent, cross_extent),
+        };
+
+        self.bounds.width = width + self.padding * 2;
+        self.bounds.height = height + self.padding * 2;
+    }
+}
+
+impl Widget for LayoutBox {
+    fn draw(&self, display: &dyn Canvas) -> Result<(), DisplayError> {
+        for child in &self.children {
+            child.draw(display)?;
+        }
+        Ok(())
+    }
+
+    fn handle_event(&mut self, event: &Event) -> bool {
+        match event {
+            //input that follows focus only goes to the child holding it
+            Event::Down | Event::Up | Event::Select | Event::EncoderRotated(_, _) => match self.focused {
+                Some(index) => self.children[index].handle_event(event),
+                None => false,
+            },
+            _ => self.children.iter_mut().any(|child| child.handle_event(event)),
+        }
+    }
+
+    fn get_bounds(&self) -> Rectangle {
+        self.bounds.clone()
+    }
+
+    fn set_position(&mut self, x: i32, y: i32) {
+        self.bounds.x = x;
+        self.bounds.y = y;
+        self.relayout();
+    }
+
+    fn is_focusable(&self) -> bool {
+        self.children.iter().any(|child| child.is_focusable())
+    }
+
+    //focus arrives on the first interactive child, focus_next/prev then walk the rest
+    fn set_focused(&mut self, focused: bool) {
+        let first = self.children.iter().position(|child| child.is_focusable());
+        self.focus_child(if focused { first } else { None });
+    }
+
+    fn focus_next(&mut self) -> bool {
+        self.step_focus(true)
+    }
+
+    fn focus_prev(&mut self) -> bool {
+        self.step_focus(false)
+    }
+
+    //only the child holding focus shows the press
+    fn set_pressed(&mut self, pressed: bool) {
+        if let Some(index) = self.focused {
+            self.children[index].set_pressed(pressed);
+        }
+    }
+
+    fn reset(&mut self) {
+        for child in &mut self.children {
+            child.reset();
+        }
+    }
+}
+
+pub trait AsAny {
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: Any> AsAny for T {
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+pub trait Screen: AsAny {
+    fn draw(&self) -> Result<(), DisplayError>;
+    fn handle_event(&mut self, event: &Event) -> bool;
+
+    fn on_enter(&mut self, _context: &mut ScreenContext) {}
+
+    fn on_exit(&mut self, _context: &mut ScreenContext) {}
+}
+
+//tasks scheduled through the context are cancelled when its screen or app is left, ones added straight to scheduler() aren't
+pub struct ScreenContext {
+    event_queue: Arc<EventQueue>,
+    scheduler: Option<Arc<Mutex<Scheduler>>>,
+    tasks: Vec<u32>,
+}
+
+impl ScreenContext {
+    pub fn new(event_queue: Arc<EventQueue>) -> Self {
+        Self {
+            event_queue,
+            scheduler: None,
+            tasks: Vec::new(),
+        }
+    }
+
+    pub fn event_queue(&self) -> &Arc<EventQueue> {
+        &self.event_queue
+    }
+
+    //None until the manager is given a scheduler
+    pub fn scheduler(&self) -> Option<&Arc<Mutex<Scheduler>>> {
+        self.scheduler.as_ref()
+    }
+
+    pub(crate) fn set_scheduler(&mut self, scheduler: Arc<Mutex<Scheduler>>) {
+        self.scheduler = Some(scheduler);
+    }
+
+    pub fn schedule_once<F>(&mut self, delay: Duration, callback: F) -> Option<u32>
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        let id = self.scheduler.as_ref()?.lock().unwrap().schedule_once(delay, callback);
+        self.tasks.push(id);
+        Some(id)
+    }
+
+    pub fn schedule_recurring<F>(&mut self, delay: Duration, interval: Duration, callback: F) -> Option<u32>
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        let id = self.scheduler.as_ref()?.lock().unwrap().schedule_recurring(delay, interval, callback);
+        self.tasks.push(id);
+        Some(id)
+    }
+
+    //one-shots that already ran are gone from the scheduler, cancelling them again is harmless
+    pub(crate) fn cancel_tasks(&mut self) {
+        let Some(scheduler) = &self.scheduler else {
+            self.tasks.clear();
+            return;
+        };
+
+        let mut scheduler = scheduler.lock().unwrap();
+        for id in self.tasks.drain(..) {
+            scheduler.cancel_task(id);
+        }
+    }
+}
+
+pub struct DefaultScreen {
+    widgets: Vec<Box<dyn Widget + Send>>,
+    focus_index: Option<usize>,
+    pressing: bool,
+    display: Arc<dyn DisplayBackend>,
+}
+
+impl DefaultScreen {
+    pub fn new(display: Arc<dyn DisplayBackend>) -> Self {
+        Self {
+            widgets: Vec::new(),
+            focus_index: None,
+            pressing: false,
+            display,
+        }
+    }
+
+    pub fn add_widget<W>(&mut self, widget: W)
+    where
+        W: Widget + Send + 'static,
+    {
+        self.widgets.push(Box::new(widget));
+
+        //first interactive widget added gets focus so the select button does something straight away
+        if self.focus_index.is_none() && self.widgets[self.widgets.len() - 1].is_focusable() {
+            self.set_focus(Some(self.widgets.len() - 1));
+        }
+    }
+
+    pub fn focused_index(&self) -> Option<usize> {
+        self.focus_index
+    }
+
+    pub fn focus_next(&mut self) {
+        self.move_focus(true);
+    }
+
+    pub fn focus_prev(&mut self) {
+        self.move_focus(false);
+    }
+
+    fn move_focus(&mut self, forward: bool) {
+        let count = self.widgets.len();
+        if count == 0 {
+            return;
+        }
+
+        //a container gets to move focus among its own children first
+        if let Some(current) = self.focus_index {
+            self.release_press();
+
+            let moved = if forward {
+                self.widgets[current].focus_next()
+            } else {
+                self.widgets[current].focus_prev()
+            };
+            if moved {
+                return;
+            }
+        }
+
+        let start = self.focus_index.unwrap_or(if forward { count - 1 } else { 0 });
+
+        for step in 1..=count {
+            let index = if forward {
+                (start + step) % count
+            } else {
+                (start + count - step % count) % count
+            };
+
+            if self.widgets[index].is_focusable() {
+                self.set_focus(Some(index));
+                if !forward {
+                    focus_last_inside(self.widgets[index].as_mut());
+                }
+                return;
+            }
+        }
+    }
+
+    fn release_press(&mut self) {
+        if !self.pressing {
+            return;
+        }
+
+        if let Some(index) = self.focus_index {
+            self.widgets[index].set_pressed(false);
+        }
+        self.pressing = false;
+    }
+
+    fn set_focus(&mut self, index: Option<usize>) {
+        //a press doesn't follow focus to another widget
+        self.release_press();
+
+        if let Some(previous) = self.focus_index {
+            self.widgets[previous].set_focused(false);
+        }
+
+        if let Some(next) = index {
+            self.widgets[next].set_focused(true);
+        }
+
+        self.focus_index = index;
+    }
+}
+
+impl Screen for DefaultScreen {
+    fn draw(&self) -> Result<(), DisplayError> {
+        //one lock and one flush for the whole frame instead of one per widget
+        self.display.draw_frame(&mut |ctx| {
+            ctx.clear()?;
+
+            for widget in &self.widgets {
+                widget.draw(ctx)?;
+            }
+
+            Ok(())
+        })
+    }
+
+    fn handle_event(&mut self, event: &Event) -> bool {
+        let handled = match event {
+            //up/down go to the focused widget first (a slider or stepper adjusts with them),
+            //focus only moves when it passes them on. select only ever goes to the focused widget
+            Event::Down | Event::Up => {
+                let consumed = match self.focus_index {
+                    Some(index) => self.widgets[index].handle_event(event),
+                    None => false,
+                };
+
+                if !consumed {
+                    self.move_focus(*event == Event::Down);
+                }
+
+                consumed || self.focus_index.is_some()
+            },
+            //select also starts the press feedback, held until the release below
+            Event::Select => {
+                match self.focus_index {
+                    Some(index) => {
+                        self.widgets[index].set_pressed(true);
+                        self.pressing = true;
+                        self.widgets[index].handle_event(event);
+                        true
+                    },
+                    None => false,
+                }
+            },
+            Event::ButtonReleased(_) if self.pressing => {
+                //widgets finish their click on the release, so it goes to them before the press is cleared
+                for widget in &mut self.widgets {
+                    if widget.handle_event(event) {
+                        break;
+                    }
+                }
+
+                self.release_press();
+                true
+            },
+            Event::EncoderRotated(_, direction) => {
+                let consumed = match self.focus_index {
+                    Some(index) => self.widgets[index].handle_event(event),
+                    None => false,
+                };
+
+                if !consumed {
+                    self.move_focus(*direction > 0);
+                }
+
+                consumed || self.focus_index.is_some()
+            },
+            _ => {
+                let mut handled = false;
+
+                for widget in &mut self.widgets {
+                    if widget.handle_event(event) {
+                        handled = true;
+                        break;
+                    }
+                }
+
+                handled
+            },
+        };
+
+        if handled {
+            let _ = self.draw();
+        }
+
+        handled
+    }
+
+    fn on_exit(&mut self, _context: &mut ScreenContext) {
+        self.release_press();
+
+        for widget in &mut self.widgets {
+            widget.reset();
+        }
+    }
+}
+
+//show_error reuses one screen under this key instead of adding a new one per failure
+const ERROR_SCREEN_KEY: &str = "error";
+
+type ShortcutAction = Box<dyn FnMut(&mut ScreenManager) -> Result<(), DisplayError> + Send>;
+
+struct GlobalShortcut {
+    matches: Box<dyn Fn(&Event) -> bool + Send>,
+    action: ShortcutAction,
+}
+
+pub struct ScreenManager {
+    screens: Vec<Box<dyn Screen + Send>>,
+    screen_keys: HashMap<String, usize>,
+    current_screen: usize,
+    entered: bool,
+    nav_stack: Vec<usize>,
+    dialog: Option<Dialog>,
+    idle_dimmer: Option<IdleDimmer>,
+    screensaver: Option<Screensaver>,
+    frame_timer: FrameTimer,
+    redraw_pending: bool,
+    apps: AppManager,
+    button_map: ButtonMap,
+    shortcuts: Vec<GlobalShortcut>,
+    screen_timeouts: HashMap<usize, (Duration, usize)>,
+    entered_at: Instant,
+    transition: Option<ScreenTransition>,
+    transition_duration: Duration,
+    context: ScreenContext,
+    display: Arc<dyn DisplayBackend>,
+    event_queue: Arc<EventQueue>,
+}
+
+impl ScreenManager {
+    pub fn new(display: Arc<dyn DisplayBackend>, event_queue: Arc<EventQueue>) -> Self {
+        Self {
+            screens: Vec::new(),
+            screen_keys: HashMap::new(),
+            current_screen: 0,
+            entered: false,
+            nav_stack: Vec::new(),
+            dialog: None,
+            idle_dimmer: None,
+            screensaver: None,
+            frame_timer: FrameTimer::default(),
+            redraw_pending: false,
+            apps: AppManager::new(event_queue.clone()),
+            context: ScreenContext::new(event_queue.clone()),
+            button_map: ButtonMap::default(),
+            shortcuts: Vec::new(),
+            screen_timeouts: HashMap::new(),
+            entered_at: Instant::now(),
+            transition: None,
+            transition_duration: DEFAULT_TRANSITION_DURATION,
+            display,
+            event_queue,
+        }
+    }
+
+    pub fn add_screen<S>(&mut self, screen: S)
+    where 
+        S: Screen + Send + 'static,
+    {
+        self.screens.push(Box::new(screen));
+    }
+
+    //the index is still returned for Event::NavigateTo, reusing a key points it at the new screen
+    pub fn add_screen_keyed<K, S>(&mut self, key: K, screen: S) -> usize
+    where
+        K: AsRef<str>,
+        S: Screen + Send + 'static,
+    {
+        let index = self.screens.len();
+        self.screens.push(Box::new(screen));
+
+        if let Some(previous) = self.screen_keys.insert(key.as_ref().to_string(), index) {
+            log::warn!("Screen key '{}' moved from screen {} to {}", key.as_ref(), previous, index);
+        }
+
+        index
+    }
+
+    pub fn screen_index<K: AsRef<str>>(&self, key: K) -> Option<usize> {
+        self.screen_keys.get(key.as_ref()).copied()
+    }
+
+    pub fn current_key(&self) -> Option<&str> {
+        self.screen_keys
+            .iter()
+            .find(|&(_, &index)| index == self.current_screen)
+            .map(|(key, _)| key.as_str())
+    }
+
+    //every keyed screen with its current index, in no particular order
+    pub fn screen_keys(&self) -> impl Iterator<Item = (&str, usize)> + '_ {
+        self.screen_keys.iter().map(|(key, &index)| (key.as_str(), index))
+    }
+
+    pub fn switch_to_screen_by_key<K: AsRef<str>>(&mut self, key: K) -> Result<(), DisplayError> {
+        match self.screen_index(&key) {
+            Some(index) => self.switch_to_screen(index),
+            None => {
+                log::error!("No screen registered as '{}'", key.as_ref());
+                Ok(())
+            },
+        }
+    }
+
+    pub fn push_screen_by_key<K: AsRef<str>>(&mut self, key: K) -> Result<(), DisplayError> {
+        match self.screen_index(&key) {
+            Some(index) => self.push_screen(index),
+            None => {
+                log::error!("No screen registered as '{}'", key.as_ref());
+                Ok(())
+            },
+        }
+    }
+
+    //later screens, history, keys and timeouts all shift down one; removing the current one lands on its replacement
+    pub fn remove_screen(&mut self, index: usize) -> Result<(), DisplayError> {
+        if index >= self.screens.len() {
+            log::warn!("No screen {} to remove ({} screens)", index, self.screens.len());
+            return Ok(());
+        }
+
+        let was_current = index == self.current_screen;
+        if was_current && self.entered && !self.apps.is_running() {
+            self.exit_active_screen();
+        }
+
+        self.screens.remove(index);
+
+        //anything that pointed past the removed screen now points one lower
+        let shift = |i: usize| if i > index { i - 1 } else { i };
+
+        self.nav_stack.retain(|&i| i != index);
+        for entry in &mut self.nav_stack {
+            *entry = shift(*entry);
+        }
+
+        self.screen_keys.retain(|_, i| *i != index);
+        for entry in self.screen_keys.values_mut() {
+            *entry = shift(*entry);
+        }
+
+        self.screen_timeouts = self.screen_timeouts
+            .drain()
+            .filter(|&(from, (_, target))| from != index && target != index)
+            .map(|(from, (timeout, target))| (shift(from), (timeout, shift(target))))
+            .collect();
+
+        if !was_current {
+            self.current_screen = shift(self.current_screen);
+            return Ok(());
+        }
+
+        if self.screens.is_empty() {
+            log::error!("Removed the last screen, nothing left to show");
+            self.current_screen = 0;
+            self.entered = false;
+            return Ok(());
+        }
+
+        //the outgoing screen already got on_exit, don't send it again to whatever slid into its slot
+        let was_entered = self.entered;
+        self.entered = false;
+        self.current_screen = index.min(self.screens.len() - 1);
+
+        if was_entered && !self.apps.is_running() {
+            self.enter_screen(self.current_screen)?;
+        } else {
+            self.entered = was_entered;
+        }
+
+        Ok(())
+    }
+
+    pub fn screen_count(&self) -> usize {
+        self.screens.len()
+    }
+
+    //None when there are no screens yet, or current_screen has gone stale
+    fn active_screen(&mut self) -> Option<&mut Box<dyn Screen + Send>> {
+        self.screens.get_mut(self.current_screen)
+    }
+
+    pub fn switch_to_screen(&mut self, index: usize) -> Result<(), DisplayError> {
+        if index < self.screens.len() {
+            self.enter_screen(index)?;
+        } else {
+            log::error!("Can't switch to screen {}, only {} registered", index, self.screens.len());
+        }
+        Ok(())
+    }
+
+    //animates over the next few process_events calls, instant if nothing's up yet, an app or dialog is, or capture fails
+    pub fn switch_to_screen_animated(&mut self, index: usize, transition: Transition) -> Result<(), DisplayError> {
+        if transition == Transition::None || !self.entered || index >= self.screens.len()
+            || self.apps.is_running() || self.dialog.is_some()
+        {
+            return self.switch_to_screen(index);
+        }
+
+        let Some(outgoing) = self.display.capture() else {
+            return self.switch_to_screen(index);
+        };
+
+        //the new screen draws into the framebuffer as usual, it just doesn't reach the panel yet
+        self.display.hold_flush(true);
+        let entered = self.enter_screen(index);
+        let incoming = self.display.capture();
+        self.display.hold_flush(false);
+        entered?;
+
+        let (width, height) = (self.display.width(), self.display.height());
+        self.transition = incoming.and_then(|incoming| {
+            ScreenTransition::new(transition, outgoing, incoming, width, height, self.transition_duration)
+        });
+
+        match self.transition {
+            Some(_) => self.step_transition(),
+            None => self.redraw(),
+        }
+    }
+
+    pub fn set_transition_duration(&mut self, duration: Duration) {
+        self.transition_duration = duration;
+    }
+
+    pub fn is_transitioning(&self) -> bool {
+        self.transition.is_some()
+    }
+
+    fn step_transition(&mut self) -> Result<(), DisplayError> {
+        let Some(transition) = &mut self.transition else {
+            return Ok(());
+        };
+
+        let running = match transition.step(self.display.as_ref()) {
+            Ok(running) => running,
+            Err(e) => {
+                self.transition = None;
+                return Err(e);
+            },
+        };
+
+        if running {
+            return Ok(());
+        }
+
+        //finish on a real draw of the new screen rather than the captured frame
+        self.transition = None;
+        self.redraw()
+    }
+
+    pub fn push_screen(&mut self, index: usize) -> Result<(), DisplayError> {
+        if index < self.screens.len() {
+            self.nav_stack.push(self.current_screen);
+            self.enter_screen(index)?;
+        } else {
+            log::error!("Can't push screen {}, only {} registered", index, self.screens.len());
+        }
+        Ok(())
+    }
+
+    pub fn pop_screen(&mut self) -> Result<(), DisplayError> {
+        if let Some(previous) = self.nav_stack.pop() {
+            self.switch_to_screen(previous)?;
+        }
+        Ok(())
+    }
+
+    fn enter_screen(&mut self, index: usize) -> Result<(), DisplayError> {
+        if index >= self.screens.len() {
+            return Ok(());
+        }
+
+        //a plain switch mid-transition wins, the animation would only paint over it
+        self.transition = None;
+
+        //nothing has been entered before the very first switch, so there's nothing to exit
+        if self.entered {
+            self.exit_active_screen();
+        }
+
+        self.current_screen = index;
+        self.entered = true;
+        self.entered_at = Instant::now();
+        self.enter_active_screen();
+        self.screens[index].draw()
+    }
+
+    fn enter_active_screen(&mut self) {
+        if let Some(screen) = self.screens.get_mut(self.current_screen) {
+            screen.on_enter(&mut self.context);
+        }
+    }
+
+    //whatever the screen scheduled through its context goes with it
+    fn exit_active_screen(&mut self) {
+        if let Some(screen) = self.screens.get_mut(self.current_screen) {
+            screen.on_exit(&mut self.context);
+        }
+        self.context.cancel_tasks();
+    }
+
+    pub fn current_index(&self) -> usize {
+        self.current_screen
+    }
+
+    pub fn register_app<A>(&mut self, app: A)
+    where
+        A: App + Send + 'static,
+    {
+        self.apps.register(app);
+    }
+
+    //a running app takes over the display and input until it's closed, the screen underneath is left on hold
+    pub fn launch_app(&mut self, id: &str) -> Result<(), DisplayError> {
+        let was_running = self.apps.is_running();
+
+        if !self.apps.launch(id) {
+            return Ok(());
+        }
+
+        if !was_running && self.entered {
+            self.exit_active_screen();
+        }
+
+        self.apps.render(self.display.as_ref())
+    }
+
+    pub fn close_app(&mut self) -> Result<(), DisplayError> {
+        if !self.apps.close() {
+            return Ok(());
+        }
+
+        if self.entered {
+            self.enter_active_screen();
+        }
+
+        self.redraw()
+    }
+
+    pub fn active_app(&self) -> Option<&str> {
+        self.apps.active_id()
+    }
+
+    pub fn show_dialog(&mut self, dialog: Dialog) -> Result<(), DisplayError> {
+        dialog.draw(&self.display)?;
+        self.display.flush_dirty()?;
+        self.dialog = Some(dialog);
+        Ok(())
+    }
+
+    /// Puts up the standard `ErrorScreen` over whatever is showing. Dismiss goes back to it,
+    /// Retry goes back and then calls `retry`. Any app or dialog is closed so the error
+    /// can't end up hidden behind it.
+    pub fn show_error<F>(&mut self, title: &str, message: &str, retry: F) -> Result<(), DisplayError>
+    where
+        F: Fn() + Send + 'static,
+    {
+        log::error!("{}: {}", title, message);
+
+        if self.apps.is_running() {
+            self.close_app()?;
+        }
+        self.dialog = None;
+
+        let screen = ErrorScreen::new(self.display.clone(), self.event_queue.clone(), title, message, retry);
+
+        match self.screen_index(ERROR_SCREEN_KEY) {
+            //already up, swap the contents in place so Dismiss still returns to what was there first
+            Some(index) if self.entered && index == self.current_screen => {
+                self.exit_active_screen();
+                self.screens[index] = Box::new(screen);
+                self.entered = false;
+                self.enter_screen(index)
+            },
+            Some(index) => {
+                self.screens[index] = Box::new(screen);
+                self.push_screen(index)
+            },
+            None => {
+                let index = self.add_screen_keyed(ERROR_SCREEN_KEY, screen);
+                self.push_screen(index)
+            },
+        }
+    }
+
+    pub fn dismiss_dialog(&mut self) -> Result<(), DisplayError> {
+        if self.dialog.take().is_some() {
+            self.redraw()?;
+        }
+        Ok(())
+    }
+
+    pub fn redraw(&mut self) -> Result<(), DisplayError> {
+        if self.apps.is_running() {
+            self.apps.render(self.display.as_ref())?;
+        } else {
+            match self.active_screen() {
+                Some(screen) => screen.draw()?,
+                None => return Ok(()),
+            }
+        }
+
+        if let Some(dialog) = &self.dialog {
+            dialog.draw(&self.display)?;
+            self.display.flush_dirty()?;
+        }
+
+        Ok(())
+    }
+
+    pub fn has_dialog(&self) -> bool {
+        self.dialog.is_some()
+    }
+
+    //screens and apps schedule through their ScreenContext from then on
+    pub fn set_scheduler(&mut self, scheduler: Arc<Mutex<Scheduler>>) {
+        self.context.set_scheduler(scheduler.clone());
+        self.apps.set_scheduler(scheduler);
+    }
+
+    pub fn set_idle_dimmer(&mut self, dimmer: IdleDimmer) {
+        self.idle_dimmer = Some(dimmer);
+    }
+
+    pub fn set_screensaver(&mut self, screensaver: Screensaver) {
+        self.screensaver = Some(screensaver);
+    }
+
+    pub fn set_button_map(&mut self, button_map: ButtonMap) {
+        self.button_map = button_map;
+    }
+
+    pub fn set_frame_rate(&mut self, fps: u32) {
+        self.frame_timer.set_fps(fps);
+    }
+
+    /// Runs `action` for any event `matches` accepts, whatever screen, app or dialog is up,
+    /// and the event goes no further. Checked against both the raw event and its
+    /// `ButtonMap` translation, so a shortcut can target either.
+    pub fn register_global_shortcut<P, A>(&mut self, matches: P, action: A)
+    where
+        P: Fn(&Event) -> bool + Send + 'static,
+        A: FnMut(&mut ScreenManager) -> Result<(), DisplayError> + Send + 'static,
+    {
+        self.shortcuts.push(GlobalShortcut {
+            matches: Box::new(matches),
+            action: Box::new(action),
+        });
+    }
+
+    //once the screen at index has been up for timeout, switch to target, e.g. a splash going home
+    pub fn set_screen_timeout(&mut self, index: usize, timeout: Duration, target: usize) {
+        self.screen_timeouts.insert(index, (timeout, target));
+    }
+
+    pub fn clear_screen_timeout(&mut self, index: usize) {
+        self.screen_timeouts.remove(&index);
+    }
+
+    fn check_screen_timeout(&mut self) -> Result<(), DisplayError> {
+        //an app or dialog on top means the screen isn't really showing, leave it be
+        if !self.entered || self.apps.is_running() || self.dialog.is_some() {
+            return Ok(());
+        }
+
+        match self.screen_timeouts.get(&self.current_screen) {
+            Some(&(timeout, target)) if self.entered_at.elapsed() >= timeout => self.switch_to_screen(target),
+            _ => Ok(()),
+        }
+    }
+
+    fn run_shortcut(&mut self, raw: &Event, translated: &Event) -> Result<bool, DisplayError> {
+        let Some(index) = self.shortcuts.iter().position(|shortcut| (shortcut.matches)(raw) || (shortcut.matches)(translated)) else {
+            return Ok(false);
+        };
+
+        //the action gets the whole manager, so lift the shortcut list out while it runs
+        let mut shortcuts = std::mem::take(&mut self.shortcuts);
+        let result = (shortcuts[index].action)(self);
+        shortcuts.append(&mut self.shortcuts);
+        self.shortcuts = shortcuts;
+
+        result.map(|_| true)
+    }
+
+    fn screensaver_active(&self) -> bool {
+        self.screensaver.as_ref().is_some_and(|screensaver| screensaver.is_active())
+    }
+
+    //returns how many events got past the screensaver, any of them may have drawn
+    fn dispatch_events(&mut self) -> Result<usize, DisplayError> {
+        let mut handed_on = 0;
+
+        //pop() only holds the queue lock briefly, handlers are allowed to push events
+        while let Some(event) = self.event_queue.pop() {
+
+            //screensaver first, it dimmed on top of the dimmer so it has to hand back its level first
+            let woke_screensaver = match &mut self.screensaver {
+                Some(screensaver) => screensaver.on_event(&event),
+                None => false,
+            };
+
+            if let Some(dimmer) = &mut self.idle_dimmer {
+                dimmer.on_event(&event);
+            }
+
+            //the input that dismisses the screensaver is swallowed, it just brings the screen back
+            //with a redraw, the screen underneath never left so it isn't entered again
+            if woke_screensaver {
+                //the rest of the batch draws as usual
+                self.display.hold_flush(false);
+                self.redraw()?;
+                continue;
+            }
+
+            handed_on += 1;
+
+            match event {
+                Event::NavigateTo(index) => {
+                    if self.apps.close() && self.entered {
+                        self.enter_active_screen();
+                    }
+                    self.push_screen(index)?;
+                    continue;
+                },
+                Event::NavigateBack => {
+                    //back out of a running app before popping screens
+                    if self.apps.is_running() {
+                        self.close_app()?;
+                    } else {
+                        self.pop_screen()?;
+                    }
+                    continue;
+                },
+                Event::LaunchApp(id) => {
+                    self.launch_app(&id)?;
+                    continue;
+                },
+                Event::Redraw => {
+                    //coalesced and paced below so a burst of requests is a single frame
+                    self.redraw_pending = true;
+                    continue;
+                },
+                _ => {},
+            }
+
+            //handlers only ever see the semantic event for a mapped press, the raw one stays with the dimmer
+            let translated = self.button_map.translate(&event);
+
+            if self.run_shortcut(&event, translated.as_ref().unwrap_or(&event))? {
+                continue;
+            }
+
+            let event = translated.unwrap_or(event);
+
+            let handled = match &mut self.dialog {
+                Some(dialog) => {
+                    let handled = dialog.handle_event(&event);
+
+                    if handled {
+                        if dialog.is_closed() {
+                            self.dismiss_dialog()?;
+                        } else {
+                            dialog.draw(&self.display)?;
+                            self.display.flush_dirty()?;
+                        }
+                    }
+                    handled
+                },
+                None if self.apps.is_running() => self.apps.handle_event(&event),
+                //no screens is a startup mistake, the event just goes unhandled
+                None => match self.active_screen() {
+                    Some(screen) => screen.handle_event(&event),
+                    None => false,
+                },
+            };
+
+            //an unclaimed Back behaves like NavigateBack
+            if !handled && event == Event::Back {
+                if self.apps.is_running() {
+                    self.close_app()?;
+                } else {
+                    self.pop_screen()?;
+                }
+            }
+        }
+
+        Ok(handed_on)
+    }
+
+    pub fn process_events(&mut self) -> Result<(), DisplayError> {
+        //input waits in the queue while a transition plays, it's handled once the new screen is up
+        if self.transition.is_some() {
+            if self.frame_timer.should_render() {
+                self.step_transition()?;
+            }
+
+            if self.transition.is_some() {
+                return Ok(());
+            }
+        }
+
+        //screens still get their events under a running screensaver, but what they draw stays off the panel
+        let screensaver_was_active = self.screensaver_active();
+        if screensaver_was_active {
+            self.display.hold_flush(true);
+        }
+
+        let screen_before = self.current_screen;
+        let dispatched = self.dispatch_events().and_then(|handed_on| {
+            self.check_screen_timeout()?;
+            Ok(handed_on > 0 || self.current_screen != screen_before)
+        });
+
+        //released on errors too, or the panel would freeze
+        if screensaver_was_active {
+            self.display.hold_flush(false);
+        }
+        let screen_touched = dispatched?;
+
+        if let Some(screensaver) = &mut self.screensaver {
+            //the held drawing still landed in the framebuffer, so paint the screensaver back over it
+            if screen_touched && screensaver.is_active() {
+                screensaver.redraw()?;
+            }
+
+            screensaver.update()?;
+
+            //redraws wait for the wake, which redraws anyway
+            if screensaver.is_active() {
+                self.redraw_pending = false;
+            }
+        }
+
+        if self.redraw_pending && self.frame_timer.should_render() {
+            self.redraw_pending = false;
+            self.redraw()?;
+        }
+
+        //a dimmer kicking in under a running screensaver would save the screensaver's level as the one to restore
+        let screensaver_active = self.screensaver_active();
+        if let Some(dimmer) = &mut self.idle_dimmer {
+            if !screensaver_active {
+                dimmer.update();
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn get_screen_as_mut<T: 'static>(&mut self) -> Option<&mut T> {
+        let screen: &mut dyn Screen = &mut **self.active_screen()?;
+        screen.as_any_mut().downcast_mut::<T>()
+    }
+}
+
+impl_drawable!(
+    Label,
+    WrappedLabel,
+    ScrollingLabel,
+    Button,
+    Checkbox,
+    ToggleSwitch,
+    Slider,
+    Stepper,
+    WifiIcon,
+    StatusBar,
+    ProgressBar,
+    Separator,
+    Gauge,
+    Spinner,
+    ScrollView,
+    Dialog,
+    LayoutBox,
+);
+
+//impl_drawable! can't take generic types
+impl<T> embedded_graphics::Drawable for ListView<T> {
+    type Color = BinaryColor;
+    type Output = ();
+
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        draw_onto(self, target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contains_excludes_the_right_and_bottom_edges() {
+        let rect = Rectangle::new(10, 10, 20, 10);
+
+        assert!(rect.contains(Point::new(10, 10)));
+        assert!(rect.contains(Point::new(29, 19)));
+        assert!(!rect.contains(Point::new(30, 15)));
+        assert!(!rect.contains(Point::new(15, 20)));
+        assert!(!rect.contains(Point::new(9, 15)));
+    }
+
+    #[test]
+    fn touching_edges_do_not_intersect() {
+        let rect = Rectangle::new(10, 10, 20, 10);
+
+        assert!(!rect.intersects(&Rectangle::new(30, 10, 5, 5)));
+        assert!(!rect.intersects(&Rectangle::new(10, 20, 5, 5)));
+        assert!(!rect.intersects(&Rectangle::new(0, 0, 10, 10)));
+        assert!(rect.intersects(&Rectangle::new(29, 19, 5, 5)));
+    }
+
+    #[test]
+    fn contained_rectangle_intersects_both_ways() {
+        let outer = Rectangle::new(0, 0, 128, 64);
+        let inner = Rectangle::new(20, 20, 10, 10);
+
+        assert!(outer.intersects(&inner));
+        assert!(inner.intersects(&outer));
+        assert!(!outer.intersects(&Rectangle::new(20, 20, 0, 10)));
+    }
+
+    #[test]
+    fn union_covers_both() {
+        let a = Rectangle::new(10, 10, 20, 10);
+
+        //touching side by side
+        assert_eq!(a.union(&Rectangle::new(30, 10, 5, 10)), Rectangle::new(10, 10, 25, 10));
+        //fully contained changes nothing, in either order
+        let inner = Rectangle::new(12, 12, 4, 4);
+        assert_eq!(a.union(&inner), a);
+        assert_eq!(inner.union(&a), a);
+        //an empty side is ignored rather than stretching it out to its origin
+        assert_eq!(a.union(&Rectangle::new(0, 0, 0, 0)), a);
+    }
+}
+
+#[cfg(all(test, feature = "simulator"))]
+mod simulator_tests {
+    use super::*;
+    use crate::drivers::simulator::SimulatorDisplay;
+    use crate::ui::screens::home::HomeScreen;
+    use crate::ui::screens::loading::LoadingScreen;
+    use crate::ui::sprite::{Sprite, SpriteAnimation};
+
+    const LOADING: usize = 0;
+    const HOME: usize = 1;
+
+    fn loading_then_home() -> ScreenManager {
+        let display: Arc<dyn DisplayBackend> = Arc::new(SimulatorDisplay::new(128, 64));
+        let event_queue = Arc::new(EventQueue::new());
+
+        let mut manager = ScreenManager::new(display.clone(), event_queue.clone());
+        manager.add_screen(LoadingScreen::new(display.clone(), event_queue.clone(), "Booting", "..."));
+        manager.add_screen(HomeScreen::new(display, event_queue, Arc::new(SystemClock::new())));
+        manager
+    }
+
+    #[test]
+    fn get_screen_as_mut_downcasts_only_the_active_screen_type() {
+        let mut manager = loading_then_home();
+
+        manager.switch_to_screen(LOADING).unwrap();
+        assert!(manager.get_screen_as_mut::<LoadingScreen>().is_some());
+        assert!(manager.get_screen_as_mut::<HomeScreen>().is_none());
+
+        manager.switch_to_screen(HOME).unwrap();
+        assert!(manager.get_screen_as_mut::<HomeScreen>().is_some());
+        assert!(manager.get_screen_as_mut::<LoadingScreen>().is_none());
+    }
+
+    const CLOCK_TIMER: u32 = 1;
+    const BLOCK: &[u8] = &[0xFF; 8];
+
+    fn block_sprite() -> Sprite {
+        Sprite::new(60, 28, 8, 8, &[BLOCK]).unwrap()
+    }
+
+    #[test]
+    fn screen_updates_stay_off_the_panel_under_the_screensaver() {
+        let display = Arc::new(SimulatorDisplay::new(128, 64));
+        let event_queue = Arc::new(EventQueue::new());
+
+        let mut home = HomeScreen::new(display.clone(), event_queue.clone(), Arc::new(SystemClock::new()));
+        home.set_clock_timer(CLOCK_TIMER);
+
+        let mut manager = ScreenManager::new(display.clone(), event_queue.clone());
+        manager.add_screen(home);
+        manager.switch_to_screen(0).unwrap();
+        manager.set_screensaver(Screensaver::new(display.clone(), block_sprite(), SpriteAnimation::new(1, 10), Duration::ZERO));
+
+        //the first pass only starts it, the sprite is painted from the next event on
+        manager.process_events().unwrap();
+        event_queue.push(Event::Timer(CLOCK_TIMER));
+        let flushes = display.flush_count();
+        manager.process_events().unwrap();
+
+        let expected = SimulatorDisplay::new(128, 64);
+        block_sprite().draw(&expected).unwrap();
+        assert_eq!(display.capture(), expected.capture(), "\n{}", display.to_ascii());
+        assert_eq!(display.flush_count(), flushes + 1);
+
+        //waking brings the home screen straight back
+        event_queue.push(Event::Select);
+        manager.process_events().unwrap();
+        assert!(display.get_pixel(5, 45), "\n{}", display.to_ascii());
+    }
+
+    #[test]
+    fn relative_widgets_follow_the_panel_they_are_drawn_on() {
+        let wide = SimulatorDisplay::new(128, 64);
+        let short = SimulatorDisplay::new(128, 32);
+
+        let label = Label::positioned("Hi", Position::Relative(0.5, 0.5), TextSize::Small, &wide);
+        let button = Button::positioned("OK", Position::Relative(0.25, 0.5), Extent::Relative(0.5, 0.25), &wide);
+        assert_eq!((label.get_bounds().x, label.get_bounds().y), (64, 32));
+        assert_eq!(button.get_bounds(), Rectangle::new(32, 32, 64, 16));
+
+        label.draw(&short).unwrap();
+        button.draw(&short).unwrap();
+        assert_eq!((label.get_bounds().x, label.get_bounds().y), (64, 16));
+        assert_eq!(button.get_bounds(), Rectangle::new(32, 16, 64, 8));
+        assert!(short.get_pixel(40, 16), "\n{}", short.to_ascii());
+    }
+
+    #[test]
+    fn set_position_pins_a_relative_widget() {
+        let wide = SimulatorDisplay::new(128, 64);
+        let mut button = Button::positioned("OK", Position::Relative(0.25, 0.5), Extent::Relative(0.5, 0.25), &wide);
+
+        button.set_position(0, 0);
+        button.draw(&SimulatorDisplay::new(128, 32)).unwrap();
 
+        assert_eq!(button.get_bounds(), Rectangle::new(0, 0, 64, 16));
     }
 }
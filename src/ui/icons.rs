@@ -0,0 +1,37 @@
+//16x16 1bpp icons, rows padded to whole bytes msb first, the format draw_bitmap expects
+
+pub const ICON_SIZE: u32 = 16;
+
+pub const GEAR: [u8; 32] = [
+    0x01, 0x80, 0x19, 0x98, 0x1F, 0xF8, 0x0F, 0xF0,
+    0x3C, 0x3C, 0x38, 0x1C, 0xF8, 0x1F, 0xF0, 0x0F,
+    0xF0, 0x0F, 0xF8, 0x1F, 0x38, 0x1C, 0x3C, 0x3C,
+    0x0F, 0xF0, 0x1F, 0xF8, 0x19, 0x98, 0x01, 0x80,
+];
+
+pub const BACK: [u8; 32] = [
+    0x00, 0x00, 0x04, 0x00, 0x0C, 0x00, 0x1C, 0x00,
+    0x3F, 0xFF, 0x7F, 0xFF, 0x3F, 0xFF, 0x1C, 0x07,
+    0x0C, 0x07, 0x04, 0x07, 0x00, 0x07, 0x00, 0x07,
+    0x03, 0xFF, 0x03, 0xFF, 0x00, 0x00, 0x00, 0x00,
+];
+
+pub const INFO: [u8; 32] = [
+    0x07, 0xE0, 0x18, 0x18, 0x20, 0x04, 0x41, 0x82,
+    0x41, 0x82, 0x80, 0x01, 0x83, 0x81, 0x81, 0x81,
+    0x81, 0x81, 0x81, 0x81, 0x41, 0x82, 0x43, 0xC2,
+    0x20, 0x04, 0x18, 0x18, 0x07, 0xE0, 0x00, 0x00,
+];
+
+//16x8 screensaver logo, open and blinking
+pub const LOGO_WIDTH: u32 = 16;
+
+pub const LOGO_OPEN: [u8; 16] = [
+    0x03, 0xC0, 0x1F, 0xF8, 0x78, 0x1E, 0xE3, 0xC7,
+    0xE3, 0xC7, 0x78, 0x1E, 0x1F, 0xF8, 0x03, 0xC0,
+];
+
+pub const LOGO_BLINK: [u8; 16] = [
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0xFF, 0xFF, 0x7F, 0xFE, 0x1B, 0x6C, 0x00, 0x00,
+];
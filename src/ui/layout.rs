@@ -0,0 +1,217 @@
+use crate::drivers::display::{Canvas, DisplayBackend, TextSize};
+use crate::ui::framework::{Button, DefaultScreen, Label, ProgressBar, Rectangle, Separator, Widget};
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+
+#[derive(Debug)]
+pub enum LayoutError {
+    UnknownWidget(usize, String),
+    BadField(usize, &'static str),
+    MissingText(usize),
+    OutOfBounds(usize),
+}
+
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayoutError::UnknownWidget(line, kind) => write!(f, "Line {}: unknown widget '{}'", line, kind),
+            LayoutError::BadField(line, field) => write!(f, "Line {}: missing or invalid {}", line, field),
+            LayoutError::MissingText(line) => write!(f, "Line {}: widget needs text", line),
+            LayoutError::OutOfBounds(line) => write!(f, "Line {}: widget doesn't fit on the display", line),
+        }
+    }
+}
+
+impl Error for LayoutError {}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum WidgetSpec {
+    Label { text: String, x: i32, y: i32, size: TextSize },
+    Button { text: String, x: i32, y: i32, width: u32, height: u32 },
+    ProgressBar { x: i32, y: i32, width: u32, progress: u8 },
+    HLine { x: i32, y: i32, length: u32 },
+    VLine { x: i32, y: i32, length: u32 },
+}
+
+//line numbers are kept so build_screen can point at the offending spec
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LayoutSpec {
+    pub widgets: Vec<(usize, WidgetSpec)>,
+}
+
+impl LayoutSpec {
+    /// One widget per line, text always last so it can contain spaces:
+    ///
+    /// ```text
+    /// # comments and blank lines are skipped
+    /// label 5 5 normal Settings
+    /// button 10 20 60 15 OK
+    /// progress 10 45 108 50
+    /// hline 0 16 128
+    /// ```
+    pub fn parse(source: &str) -> Result<Self, LayoutError> {
+        let mut widgets = Vec::new();
+
+        for (index, line) in source.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut fields = Fields::new(line, line_number);
+            let kind = fields.word();
+
+            let spec = match kind {
+                "label" => WidgetSpec::Label {
+                    x: fields.number("x")?,
+                    y: fields.number("y")?,
+                    size: fields.text_size()?,
+                    text: fields.text()?,
+                },
+                "button" => WidgetSpec::Button {
+                    x: fields.number("x")?,
+                    y: fields.number("y")?,
+                    width: fields.number("width")?,
+                    height: fields.number("height")?,
+                    text: fields.text()?,
+                },
+                "progress" => WidgetSpec::ProgressBar {
+                    x: fields.number("x")?,
+                    y: fields.number("y")?,
+                    width: fields.number("width")?,
+                    progress: fields.number("progress")?,
+                },
+                "hline" => WidgetSpec::HLine {
+                    x: fields.number("x")?,
+                    y: fields.number("y")?,
+                    length: fields.number("length")?,
+                },
+                "vline" => WidgetSpec::VLine {
+                    x: fields.number("x")?,
+                    y: fields.number("y")?,
+                    length: fields.number("length")?,
+                },
+                other => return Err(LayoutError::UnknownWidget(line_number, other.to_string())),
+            };
+
+            widgets.push((line_number, spec));
+        }
+
+        Ok(Self { widgets })
+    }
+}
+
+struct Fields<'a> {
+    rest: &'a str,
+    line: usize,
+}
+
+impl<'a> Fields<'a> {
+    fn new(line: &'a str, line_number: usize) -> Self {
+        Self { rest: line, line: line_number }
+    }
+
+    fn word(&mut self) -> &'a str {
+        let rest = self.rest.trim_start();
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        self.rest = &rest[end..];
+        &rest[..end]
+    }
+
+    fn number<T: std::str::FromStr>(&mut self, field: &'static str) -> Result<T, LayoutError> {
+        self.word().parse().map_err(|_| LayoutError::BadField(self.line, field))
+    }
+
+    fn text_size(&mut self) -> Result<TextSize, LayoutError> {
+        match self.word() {
+            "small" => Ok(TextSize::Small),
+            "normal" => Ok(TextSize::Normal),
+            "large" => Ok(TextSize::Large),
+            _ => Err(LayoutError::BadField(self.line, "size")),
+        }
+    }
+
+    fn text(&mut self) -> Result<String, LayoutError> {
+        let text = self.rest.trim();
+        if text.is_empty() {
+            return Err(LayoutError::MissingText(self.line));
+        }
+        Ok(text.to_string())
+    }
+}
+
+//every widget has to sit entirely on the panel, anything hanging off the edge is rejected rather than clipped
+pub fn build_screen(spec: &LayoutSpec, display: Arc<dyn DisplayBackend>) -> Result<DefaultScreen, LayoutError> {
+    let (width, height) = (display.width(), display.height());
+    let mut screen = DefaultScreen::new(display);
+
+    for (line, widget) in &spec.widgets {
+        match widget {
+            WidgetSpec::Label { text, x, y, size } => {
+                let label = Label::new(text, *x, *y, *size);
+                check_fits(*line, &label.get_bounds(), width, height)?;
+                screen.add_widget(label);
+            },
+            WidgetSpec::Button { text, x, y, width: button_width, height: button_height } => {
+                let button = Button::new(text, *x, *y, *button_width, *button_height);
+                check_fits(*line, &button.get_bounds(), width, height)?;
+                screen.add_widget(button);
+            },
+            WidgetSpec::ProgressBar { x, y, width: bar_width, progress } => {
+                let bar = ProgressBar::new(*x, *y, *bar_width, *progress);
+                check_fits(*line, &bar.get_bounds(), width, height)?;
+                screen.add_widget(bar);
+            },
+            WidgetSpec::HLine { x, y, length } => {
+                let separator = Separator::horizontal(*x, *y, *length);
+                check_fits(*line, &separator.get_bounds(), width, height)?;
+                screen.add_widget(separator);
+            },
+            WidgetSpec::VLine { x, y, length } => {
+                let separator = Separator::vertical(*x, *y, *length);
+                check_fits(*line, &separator.get_bounds(), width, height)?;
+                screen.add_widget(separator);
+            },
+        }
+    }
+
+    Ok(screen)
+}
+
+fn check_fits(line: usize, bounds: &Rectangle, width: u32, height: u32) -> Result<(), LayoutError> {
+    //a spec can ask for sizes near u32::MAX, so an edge that overflows just doesn't fit
+    let within = |start: i32, extent: u32, limit: u32| {
+        start >= 0 && (start as u32).checked_add(extent).is_some_and(|end| end <= limit)
+    };
+    let fits = within(bounds.x, bounds.width, width) && within(bounds.y, bounds.height, height);
+
+    if fits {
+        Ok(())
+    } else {
+        Err(LayoutError::OutOfBounds(line))
+    }
+}
+
+#[cfg(all(test, feature = "simulator"))]
+mod tests {
+    use super::*;
+    use crate::drivers::simulator::SimulatorDisplay;
+
+    fn build(source: &str) -> Result<DefaultScreen, LayoutError> {
+        build_screen(&LayoutSpec::parse(source)?, Arc::new(SimulatorDisplay::new(128, 64)))
+    }
+
+    #[test]
+    fn widget_that_fits_builds() {
+        assert!(build("button 10 20 60 15 OK").is_ok());
+    }
+
+    #[test]
+    fn overflowing_size_is_out_of_bounds_not_a_panic() {
+        assert!(matches!(build("\nbutton 10 0 4294967295 10 OK"), Err(LayoutError::OutOfBounds(2))));
+        assert!(matches!(build("button 0 10 10 4294967295 OK"), Err(LayoutError::OutOfBounds(1))));
+    }
+}
@@ -1,3 +1,9 @@
 pub mod framework;
 pub mod animations;
 pub mod screens;
+pub mod apps;
+pub mod icons;
+pub mod sprite;
+pub mod screensaver;
+pub mod layout;
+pub mod transition;
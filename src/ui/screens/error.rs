@@ -0,0 +1,62 @@
+use crate::drivers::display::{DisplayBackend, DisplayError, TextSize};
+use crate::ui::framework::{Button, DefaultScreen, Label, Screen, ScreenContext, Separator, WrappedLabel};
+use crate::system::events::{Event, EventQueue};
+use std::sync::Arc;
+
+const BUTTON_WIDTH: u32 = 56;
+const BUTTON_HEIGHT: u32 = 14;
+
+//standard failure screen: what broke, why, and a way to try again or get out
+pub struct ErrorScreen {
+    screen: DefaultScreen,
+}
+
+impl ErrorScreen {
+    /// Retry runs `retry` after leaving the screen, Dismiss just leaves it. Both go back
+    /// through `Event::NavigateBack`, so the screen should be pushed rather than switched to.
+    pub fn new<F>(display: Arc<dyn DisplayBackend>, event_queue: Arc<EventQueue>, title: &str, message: &str, retry: F) -> Self
+    where
+        F: Fn() + Send + 'static,
+    {
+        let width = display.width();
+        let button_y = display.height() as i32 - BUTTON_HEIGHT as i32 - 2;
+
+        let mut retry_button = Button::new("Retry", 4, button_y, BUTTON_WIDTH, BUTTON_HEIGHT);
+        let retry_queue = event_queue.clone();
+        retry_button.set_on_click(move || {
+            retry_queue.push(Event::NavigateBack);
+            retry();
+        });
+
+        let mut dismiss_button = Button::new("Dismiss", width as i32 - BUTTON_WIDTH as i32 - 4, button_y, BUTTON_WIDTH, BUTTON_HEIGHT);
+        dismiss_button.set_on_click(move || event_queue.push(Event::NavigateBack));
+
+        //retry goes in first so it has focus, the obvious next move after a failure
+        let mut screen = DefaultScreen::new(display);
+        screen.add_widget(Label::new(title, 4, 2, TextSize::Normal));
+        screen.add_widget(Separator::horizontal(0, 13, width));
+        screen.add_widget(WrappedLabel::new(message, 4, 16, width - 8, TextSize::Small));
+        screen.add_widget(retry_button);
+        screen.add_widget(dismiss_button);
+
+        Self { screen }
+    }
+}
+
+impl Screen for ErrorScreen {
+    fn draw(&self) -> Result<(), DisplayError> {
+        self.screen.draw()
+    }
+
+    fn handle_event(&mut self, event: &Event) -> bool {
+        self.screen.handle_event(event)
+    }
+
+    fn on_enter(&mut self, context: &mut ScreenContext) {
+        self.screen.on_enter(context);
+    }
+
+    fn on_exit(&mut self, context: &mut ScreenContext) {
+        self.screen.on_exit(context);
+    }
+}
@@ -1,6 +1,7 @@
-use crate::drivers::display::{DisplayManager, DisplayError, TextSize};
-use crate::ui::framework::{Button, Label, Screen, Widget};
-use crate::system::events::Event;
+use crate::drivers::display::{Canvas, DisplayBackend, DisplayError, TextSize};
+use crate::ui::framework::{Button, Label, Screen, StatusBar, Widget};
+use crate::system::events::{Event, EventQueue};
+use crate::system::clock::SystemClock;
 use std::sync::Arc;
 
 pub struct HomeScreen {
@@ -8,22 +9,33 @@ pub struct HomeScreen {
     status: Label,
     menu_button: Button,
     settings_button: Button,
-    display: Arc<DisplayManager>,
+    settings_selected: bool,
+    status_bar: Option<StatusBar>,
+    uptime: Label,
+    clock_timer: Option<u32>,
+    system_clock: Arc<SystemClock>,
+    display: Arc<dyn DisplayBackend>,
+    event_queue: Arc<EventQueue>,
     counter: u32,
 }
 
 impl HomeScreen {
-    pub fn new(display: Arc<DisplayManager>) -> Self {
+    pub fn new(display: Arc<dyn DisplayBackend>, event_queue: Arc<EventQueue>, system_clock: Arc<SystemClock>) -> Self {
         let mut screen = Self {
             title: Label::new("visionHub OS Home", 5, 5, TextSize::Normal),
             status: Label::new("System Ready", 5, 20, TextSize::Small),
             menu_button: Button::new("Menu", 5, 35, 50, 20),
             settings_button: Button::new("Settings", 70, 35, 50, 20),
+            settings_selected: false,
+            status_bar: Some(StatusBar::new("visionHub", display.width(), system_clock.clone())),
+            uptime: Label::new("Up 00:00:00", 5, 56, TextSize::Small),
+            clock_timer: None,
+            system_clock,
             display,
+            event_queue,
             counter: 0,
         };
 
-        let counter = screen.counter;
         screen.menu_button.set_on_click(move || {
             log::info!("Menu button clicked");
         });
@@ -32,9 +44,49 @@ impl HomeScreen {
             log::info!("Settings button clicked");
         });
 
+        screen.menu_button.set_focused(true);
+
         screen
     }
 
+    //falls back to the plain title label in the space the bar would have used
+    pub fn hide_status_bar(&mut self) {
+        self.status_bar = None;
+    }
+
+    pub fn set_settings_screen(&mut self, index: usize) {
+        let event_queue = self.event_queue.clone();
+
+        self.settings_button.set_on_click(move || {
+            log::info!("Settings button clicked");
+            event_queue.push(Event::NavigateTo(index));
+        });
+    }
+
+    pub fn set_menu_app(&mut self, app_id: &str) {
+        let event_queue = self.event_queue.clone();
+        let app_id = app_id.to_string();
+
+        self.menu_button.set_on_click(move || {
+            log::info!("Menu button clicked");
+            event_queue.push(Event::LaunchApp(app_id.clone()));
+        });
+    }
+
+    //the uptime label only ticks when Timer events with this id arrive
+    pub fn set_clock_timer(&mut self, timer_id: u32) {
+        self.clock_timer = Some(timer_id);
+    }
+
+    fn update_uptime(&mut self) -> Result<(), DisplayError> {
+        self.uptime.set_text(&format!("Up {}", self.system_clock.format_uptime()));
+
+        let bounds = self.uptime.get_bounds();
+        self.display.clear_region(bounds.x, bounds.y, bounds.width, bounds.height)?;
+        self.uptime.draw(&self.display)?;
+        self.display.flush_dirty()
+    }
+
     pub fn update_status(&mut self, status: &str) {
         self.status.set_text(status);
     }
@@ -42,35 +94,128 @@ impl HomeScreen {
     pub fn increment_counter(&mut self) {
         self.counter += 1;
         self.update_status(&format!("Count: {}", self.counter));
+        let _ = self.redraw_status();
+    }
+
+    fn redraw_status(&self) -> Result<(), DisplayError> {
+        let bounds = self.status.get_bounds();
+
+        //status text changes width as it updates, so wipe the whole row
+        self.display.clear_region(0, bounds.y, self.display.width(), bounds.height)?;
+        self.status.draw(&self.display)?;
+        self.display.flush_dirty()
+    }
+
+    fn update_status_bar(&mut self, event: &Event) {
+        let changed = match &mut self.status_bar {
+            Some(status_bar) => status_bar.handle_event(event),
+            None => false,
+        };
+
+        if changed {
+            let _ = self.redraw_status_bar();
+        }
     }
 
+    fn redraw_status_bar(&self) -> Result<(), DisplayError> {
+        let status_bar = match &self.status_bar {
+            Some(status_bar) => status_bar,
+            None => return Ok(()),
+        };
 
+        //the bar clears its own strip before drawing
+        status_bar.draw(&self.display)?;
+        self.display.flush_dirty()
+    }
 }
 
 impl Screen for HomeScreen {
     fn draw(&self) -> Result<(), DisplayError> {
         self.display.clear()?;
 
-        self.title.draw(&self.display)?;
+        match &self.status_bar {
+            Some(status_bar) => status_bar.draw(&self.display)?,
+            None => self.title.draw(&self.display)?,
+        }
+
         self.status.draw(&self.display)?;
         self.menu_button.draw(&self.display)?;
         self.settings_button.draw(&self.display)?;
+        self.uptime.draw(&self.display)?;
 
         self.display.flush()
     }
 
     fn handle_event(&mut self, event: &Event) -> bool {
         match event {
-            Event::ButtonPressed(pin) if *pin == 26 => {
-                self.menu_button.handle_event(event);
-                self.increment_counter();
+            Event::Up | Event::Down => {
+                self.settings_selected = !self.settings_selected;
+                self.menu_button.set_focused(!self.settings_selected);
+                self.settings_button.set_focused(self.settings_selected);
+                let _ = self.draw();
+                true
+            },
+            Event::Select => {
+                if self.settings_selected {
+                    self.settings_button.handle_event(event);
+                } else {
+                    self.menu_button.handle_event(event);
+                    self.increment_counter();
+                }
+                true
+            },
+            Event::ButtonReleased(_) => {
+                self.menu_button.handle_event(event) || self.settings_button.handle_event(event)
+            },
+            Event::Timer(id) if Some(*id) == self.clock_timer => {
+                let _ = self.update_uptime();
+                self.update_status_bar(event);
                 true
             },
-            Event::ButtonReleased(pin) if *pin == 26 => {
-                self.menu_button.handle_event(event);
+            Event::NetworkUp | Event::NetworkDown | Event::NetworkSignal(_) | Event::BatteryLevel(_) | Event::BatteryLow(_) | Event::TimeSynced => {
+                self.update_status_bar(event);
                 true
             },
             _ => false,
         }
     }
 }
+
+#[cfg(all(test, feature = "simulator"))]
+mod tests {
+    use super::*;
+    use crate::drivers::simulator::SimulatorDisplay;
+
+    //left edge of each button's focus ring, two pixels outside the button, halfway down
+    const MENU_RING: (i32, i32) = (3, 45);
+    const SETTINGS_RING: (i32, i32) = (68, 45);
+
+    fn home_screen() -> (Arc<SimulatorDisplay>, HomeScreen) {
+        let display = Arc::new(SimulatorDisplay::new(128, 64));
+        let screen = HomeScreen::new(display.clone(), Arc::new(EventQueue::new()), Arc::new(SystemClock::new()));
+        (display, screen)
+    }
+
+    #[test]
+    fn draw_outlines_both_buttons_and_rings_menu() {
+        let (display, screen) = home_screen();
+        screen.draw().unwrap();
+
+        assert!(display.get_pixel(5, 45), "menu button outline\n{}", display.to_ascii());
+        assert!(display.get_pixel(70, 45), "settings button outline\n{}", display.to_ascii());
+        assert!(display.get_pixel(MENU_RING.0, MENU_RING.1));
+        assert!(!display.get_pixel(SETTINGS_RING.0, SETTINGS_RING.1));
+        assert_eq!(display.flush_count(), 1);
+    }
+
+    #[test]
+    fn down_moves_the_focus_ring_to_settings() {
+        let (display, mut screen) = home_screen();
+        screen.draw().unwrap();
+
+        assert!(screen.handle_event(&Event::Down));
+
+        assert!(!display.get_pixel(MENU_RING.0, MENU_RING.1), "{}", display.to_ascii());
+        assert!(display.get_pixel(SETTINGS_RING.0, SETTINGS_RING.1));
+    }
+}
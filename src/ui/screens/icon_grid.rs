@@ -0,0 +1,153 @@
+use crate::drivers::display::{font_metrics, text_width, Canvas, DisplayBackend, DisplayError, TextSize};
+use crate::ui::framework::{Label, Screen, Widget};
+use crate::system::events::Event;
+use std::sync::Arc;
+
+const GRID_TOP: i32 = 14;
+
+pub struct IconItem {
+    label: String,
+    icon: &'static [u8],
+    icon_width: u32,
+    action: Box<dyn Fn() + Send>,
+}
+
+pub struct IconGridScreen {
+    title: Label,
+    items: Vec<IconItem>,
+    columns: usize,
+    rows: usize,
+    display: Arc<dyn DisplayBackend>,
+    selected_index: usize,
+}
+
+impl IconGridScreen {
+    //rows is how many fit on screen at once, extra items page in as the selection moves past them
+    pub fn new(display: Arc<dyn DisplayBackend>, title: &str, columns: usize, rows: usize) -> Self {
+        Self {
+            title: Label::new(title, 5, 2, TextSize::Normal),
+            items: Vec::new(),
+            columns: columns.max(1),
+            rows: rows.max(1),
+            display,
+            selected_index: 0,
+        }
+    }
+
+    pub fn add_item<F>(&mut self, label: &str, icon: &'static [u8], icon_width: u32, action: F)
+    where
+        F: Fn() + Send + 'static,
+    {
+        self.items.push(IconItem {
+            label: label.to_string(),
+            icon,
+            icon_width,
+            action: Box::new(action),
+        });
+    }
+
+    pub fn select_next(&mut self) {
+        if !self.items.is_empty() {
+            self.selected_index = (self.selected_index + 1) % self.items.len();
+            let _ = self.draw();
+        }
+    }
+
+    pub fn select_prev(&mut self) {
+        if !self.items.is_empty() {
+            self.selected_index = if self.selected_index == 0 {
+                self.items.len() - 1
+            } else {
+                self.selected_index - 1
+            };
+            let _ = self.draw();
+        }
+    }
+
+    //moves by whole cells, wrapping around each edge of the grid
+    pub fn move_selection(&mut self, columns: i32, rows: i32) {
+        if self.items.is_empty() {
+            return;
+        }
+
+        let total_columns = self.columns as i32;
+        let total_rows = self.items.len().div_ceil(self.columns) as i32;
+
+        let column = (self.selected_index % self.columns) as i32;
+        let row = (self.selected_index / self.columns) as i32;
+
+        let column = (column + columns).rem_euclid(total_columns);
+        let row = (row + rows).rem_euclid(total_rows);
+
+        //the last row can be partly empty, land on its last item instead
+        let index = (row * total_columns + column) as usize;
+        self.selected_index = index.min(self.items.len() - 1);
+        let _ = self.draw();
+    }
+
+    pub fn activate_selected(&mut self) {
+        if let Some(item) = self.items.get(self.selected_index) {
+            (item.action)();
+        }
+    }
+
+    fn cell_size(&self) -> (u32, u32) {
+        let width = self.display.width() / self.columns as u32;
+        let height = (self.display.height() - GRID_TOP as u32) / self.rows as u32;
+        (width, height)
+    }
+}
+
+impl Screen for IconGridScreen {
+    fn draw(&self) -> Result<(), DisplayError> {
+        let (cell_width, cell_height) = self.cell_size();
+        let page_size = self.columns * self.rows;
+        let first = (self.selected_index / page_size) * page_size;
+
+        self.display.draw_frame(&mut |ctx| {
+            ctx.clear()?;
+            self.title.draw(ctx)?;
+
+            for (slot, item) in self.items.iter().enumerate().skip(first).take(page_size) {
+                let slot = slot - first;
+                let cell_x = (slot % self.columns) as i32 * cell_width as i32;
+                let cell_y = GRID_TOP + (slot / self.columns) as i32 * cell_height as i32;
+
+                let bytes_per_row = item.icon_width.div_ceil(8) as usize;
+                let icon_height = (item.icon.len() / bytes_per_row.max(1)) as u32;
+                let icon_x = cell_x + (cell_width as i32 - item.icon_width as i32) / 2;
+                let icon_y = cell_y + (cell_height.saturating_sub(icon_height + font_metrics(TextSize::Small).1) / 2) as i32;
+                ctx.draw_bitmap(icon_x, icon_y, item.icon_width, item.icon)?;
+
+                let label_width = text_width(&item.label, TextSize::Small) as i32;
+                let label_x = cell_x + (cell_width as i32 - label_width).max(0) / 2;
+                let label_y = icon_y + icon_height as i32 + 1;
+                ctx.draw_text_clipped(&item.label, label_x, label_y, TextSize::Small, cell_x, cell_width)?;
+
+                if slot + first == self.selected_index {
+                    ctx.draw_rectangle(cell_x, cell_y, cell_width, cell_height, false)?;
+                }
+            }
+
+            Ok(())
+        })
+    }
+
+    fn handle_event(&mut self, event: &Event) -> bool {
+        match event {
+            Event::Select => {
+                self.activate_selected();
+                true
+            },
+            Event::Down => {
+                self.select_next();
+                true
+            },
+            Event::Up => {
+                self.select_prev();
+                true
+            },
+            _ => false,
+        }
+    }
+}
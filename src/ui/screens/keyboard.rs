@@ -0,0 +1,301 @@
+use crate::drivers::display::{font_metrics, text_width, Canvas, DisplayBackend, DisplayError, TextSize};
+use crate::ui::framework::{Screen, ScreenContext};
+use crate::system::events::Event;
+use std::sync::Arc;
+
+const LOWER_KEYS: &str = "abcdefghijklmnopqrstuvwxyz.-_@";
+const UPPER_KEYS: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ.-_@";
+const SYMBOL_KEYS: &str = "0123456789!#$%&*+/=?^~()[]{}<>:;,'\"|\\`";
+
+const COLUMNS: usize = 10;
+const TEXT_Y: i32 = 1;
+const GRID_TOP: i32 = 13;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyboardMode {
+    Lower,
+    Upper,
+    Symbols,
+}
+
+impl KeyboardMode {
+    fn keys(self) -> &'static str {
+        match self {
+            KeyboardMode::Lower => LOWER_KEYS,
+            KeyboardMode::Upper => UPPER_KEYS,
+            KeyboardMode::Symbols => SYMBOL_KEYS,
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            KeyboardMode::Lower => KeyboardMode::Upper,
+            KeyboardMode::Upper => KeyboardMode::Symbols,
+            KeyboardMode::Symbols => KeyboardMode::Lower,
+        }
+    }
+}
+
+//the bottom row, after the character grid in selection order
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SpecialKey {
+    Mode,
+    Space,
+    Delete,
+    Done,
+}
+
+const SPECIAL_KEYS: [SpecialKey; 4] = [SpecialKey::Mode, SpecialKey::Space, SpecialKey::Delete, SpecialKey::Done];
+
+enum Key {
+    Char(char),
+    Special(SpecialKey),
+}
+
+/// Text entry with just scroll and select: Down/Up walk through the keys in reading order,
+/// a Select press types the key on release, and holding Select long enough for a
+/// `ButtonLongPressed` switches between lowercase, uppercase and symbols instead.
+pub struct VirtualKeyboard {
+    text: String,
+    mode: KeyboardMode,
+    selected: usize,
+    pressing: bool,
+    masked: bool,
+    max_length: Option<usize>,
+    on_submit: Option<Box<dyn Fn(String) + Send>>,
+    display: Arc<dyn DisplayBackend>,
+}
+
+impl VirtualKeyboard {
+    pub fn new(display: Arc<dyn DisplayBackend>) -> Self {
+        Self {
+            text: String::new(),
+            mode: KeyboardMode::Lower,
+            selected: 0,
+            pressing: false,
+            masked: false,
+            max_length: None,
+            on_submit: None,
+            display,
+        }
+    }
+
+    pub fn with_text(mut self, text: &str) -> Self {
+        self.text = text.to_string();
+        self
+    }
+
+    //shows * for everything typed, for passwords
+    pub fn with_masked(mut self, masked: bool) -> Self {
+        self.masked = masked;
+        self
+    }
+
+    //further keys are ignored once the buffer is full
+    pub fn with_max_length(mut self, max_length: usize) -> Self {
+        self.max_length = Some(max_length);
+        self
+    }
+
+    //called with the whole buffer when Done is picked, the buffer is left as it is
+    pub fn set_on_submit<F>(&mut self, callback: F)
+    where
+        F: Fn(String) + Send + 'static,
+    {
+        self.on_submit = Some(Box::new(callback));
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn clear(&mut self) {
+        self.text.clear();
+    }
+
+    pub fn mode(&self) -> KeyboardMode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: KeyboardMode) {
+        let old_count = self.mode.keys().chars().count();
+        let new_count = mode.keys().chars().count();
+
+        //the bottom row stays put, a character falls back to the last one if the new grid is shorter
+        self.selected = if self.selected >= old_count {
+            new_count + (self.selected - old_count)
+        } else {
+            self.selected.min(new_count - 1)
+        };
+
+        self.mode = mode;
+    }
+
+    pub fn next_mode(&mut self) {
+        self.set_mode(self.mode.next());
+    }
+
+    fn key_count(&self) -> usize {
+        self.mode.keys().chars().count() + SPECIAL_KEYS.len()
+    }
+
+    fn key(&self, index: usize) -> Option<Key> {
+        let chars = self.mode.keys().chars().count();
+
+        if index < chars {
+            self.mode.keys().chars().nth(index).map(Key::Char)
+        } else {
+            SPECIAL_KEYS.get(index - chars).copied().map(Key::Special)
+        }
+    }
+
+    fn special_label(&self, key: SpecialKey) -> &'static str {
+        match key {
+            //names the mode it switches to
+            SpecialKey::Mode => match self.mode.next() {
+                KeyboardMode::Lower => "abc",
+                KeyboardMode::Upper => "ABC",
+                KeyboardMode::Symbols => "#+=",
+            },
+            SpecialKey::Space => "Spc",
+            SpecialKey::Delete => "Del",
+            SpecialKey::Done => "OK",
+        }
+    }
+
+    pub fn select_next(&mut self) {
+        self.selected = (self.selected + 1) % self.key_count();
+        let _ = self.draw();
+    }
+
+    pub fn select_prev(&mut self) {
+        self.selected = if self.selected == 0 {
+            self.key_count() - 1
+        } else {
+            self.selected - 1
+        };
+        let _ = self.draw();
+    }
+
+    pub fn activate_selected(&mut self) {
+        match self.key(self.selected) {
+            Some(Key::Char(c)) => self.type_char(c),
+            Some(Key::Special(SpecialKey::Mode)) => self.next_mode(),
+            Some(Key::Special(SpecialKey::Space)) => self.type_char(' '),
+            Some(Key::Special(SpecialKey::Delete)) => {
+                self.text.pop();
+            },
+            Some(Key::Special(SpecialKey::Done)) => {
+                if let Some(callback) = &self.on_submit {
+                    callback(self.text.clone());
+                }
+            },
+            None => {},
+        }
+    }
+
+    fn type_char(&mut self, c: char) {
+        if self.max_length.is_some_and(|max| self.text.chars().count() >= max) {
+            return;
+        }
+        self.text.push(c);
+    }
+
+    //the tail of the buffer with a cursor, whatever fits on one line
+    fn visible_text(&self) -> String {
+        let (char_width, _) = font_metrics(TextSize::Small);
+        let fits = (self.display.width() / char_width) as usize - 1;
+
+        let shown: String = if self.masked {
+            "*".repeat(self.text.chars().count())
+        } else {
+            self.text.clone()
+        };
+
+        let skip = shown.chars().count().saturating_sub(fits);
+        let mut visible: String = shown.chars().skip(skip).collect();
+        visible.push('_');
+        visible
+    }
+
+    fn draw_key(ctx: &dyn Canvas, label: &str, x: i32, y: i32, width: u32, height: u32, selected: bool) -> Result<(), DisplayError> {
+        let (_, line_height) = font_metrics(TextSize::Small);
+        let text_x = x + (width as i32 - text_width(label, TextSize::Small) as i32) / 2;
+        let text_y = y + (height as i32 - line_height as i32) / 2;
+
+        if selected {
+            ctx.draw_rectangle(x, y, width, height, true)?;
+            ctx.draw_text_inverted(label, text_x, text_y, TextSize::Small)
+        } else {
+            ctx.draw_text(label, text_x, text_y, TextSize::Small)
+        }
+    }
+}
+
+impl Screen for VirtualKeyboard {
+    fn draw(&self) -> Result<(), DisplayError> {
+        let width = self.display.width();
+        let (_, line_height) = font_metrics(TextSize::Small);
+        let row_height = line_height + 2;
+        let cell_width = width / COLUMNS as u32;
+
+        self.display.draw_frame(&mut |ctx| {
+            ctx.clear()?;
+            ctx.draw_text(&self.visible_text(), 2, TEXT_Y, TextSize::Small)?;
+            ctx.draw_hline(0, GRID_TOP - 2, width)?;
+
+            let mut buf = [0; 4];
+            for (index, c) in self.mode.keys().chars().enumerate() {
+                let x = (index % COLUMNS) as i32 * cell_width as i32;
+                let y = GRID_TOP + (index / COLUMNS) as i32 * row_height as i32;
+                Self::draw_key(ctx, c.encode_utf8(&mut buf), x, y, cell_width, row_height, index == self.selected)?;
+            }
+
+            let chars = self.mode.keys().chars().count();
+            let special_y = GRID_TOP + chars.div_ceil(COLUMNS) as i32 * row_height as i32;
+            let special_width = width / SPECIAL_KEYS.len() as u32;
+
+            for (slot, key) in SPECIAL_KEYS.iter().enumerate() {
+                let x = slot as i32 * special_width as i32;
+                Self::draw_key(ctx, self.special_label(*key), x, special_y, special_width, row_height, chars + slot == self.selected)?;
+            }
+
+            Ok(())
+        })
+    }
+
+    fn handle_event(&mut self, event: &Event) -> bool {
+        match event {
+            Event::Down => {
+                self.select_next();
+                true
+            },
+            Event::Up => {
+                self.select_prev();
+                true
+            },
+            //typing waits for the release, so a long press can turn into a mode switch instead
+            Event::Select => {
+                self.pressing = true;
+                true
+            },
+            Event::ButtonLongPressed(_) => {
+                self.pressing = false;
+                self.next_mode();
+                let _ = self.draw();
+                true
+            },
+            Event::ButtonReleased(_) if self.pressing => {
+                self.pressing = false;
+                self.activate_selected();
+                let _ = self.draw();
+                true
+            },
+            _ => false,
+        }
+    }
+
+    fn on_exit(&mut self, _context: &mut ScreenContext) {
+        self.pressing = false;
+    }
+}
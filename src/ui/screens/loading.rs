@@ -1,32 +1,124 @@
-use crate::drivers::display::{DisplayManager, DisplayError, TextSize};
-use crate::ui::framework::{Label, ProgressBar, Screen, Widget};
-use crate::system::events::Event;
+use crate::drivers::display::{font_metrics, Canvas, DisplayBackend, DisplayError, TextSize};
+use crate::ui::framework::{Label, ProgressBar, Screen, ScreenContext, Widget};
+use crate::system::events::{Event, EventQueue};
+use crate::system::time::FrameTimer;
 use std::sync::Arc;
-use std::time::Duration;
+
+const LOGO_TOP: i32 = 2;
+const ROW_GAP: i32 = 2;
+
+struct Logo {
+    data: &'static [u8],
+    x: i32,
+    width: u32,
+}
+
+struct BootStep {
+    name: String,
+    weight: u32,
+    done: bool,
+}
 
 pub struct LoadingScreen {
+    logo: Option<Logo>,
     title: Label,
     message: Label,
     progress_bar: ProgressBar,
-    display: Arc<DisplayManager>,
+    display: Arc<dyn DisplayBackend>,
+    event_queue: Arc<EventQueue>,
     progress: u8,
-    step_duration: Duration,
-    last_update: std::time::Instant,
+    step_timer: FrameTimer,
+    steps: Vec<BootStep>,
 }
 
 impl LoadingScreen {
-    pub fn new(display: Arc<DisplayManager>, title: &str, message: &str) -> Self {
+    pub fn new(display: Arc<dyn DisplayBackend>, event_queue: Arc<EventQueue>, title: &str, message: &str) -> Self {
         Self {
+            logo: None,
             title: Label::new(title, 10, 10, TextSize::Normal),
             message: Label::new(message, 10, 30, TextSize::Small),
             progress_bar: ProgressBar::new(10, 45, 108, 0),
             display,
+            event_queue,
             progress: 0,
-            step_duration: Duration::from_millis(100),
-            last_update: std::time::Instant::now(),
+            //one progress step every 100ms
+            step_timer: FrameTimer::new(10),
+            steps: Vec::new(),
         }
     }
 
+    /// Registers a named part of boot. Once any step is added the bar stops advancing on its
+    /// own and only moves as steps complete, each worth `weight` out of the total.
+    pub fn add_step(&mut self, name: &str, weight: u32) {
+        self.steps.push(BootStep {
+            name: name.to_string(),
+            weight: weight.max(1),
+            done: false,
+        });
+    }
+
+    /// Marks `name` done, redraws, and pushes `Event::BootComplete` once every step is.
+    /// The message moves on to whichever step is still outstanding.
+    pub fn complete_step(&mut self, name: &str) -> Result<(), DisplayError> {
+        let Some(step) = self.steps.iter_mut().find(|step| step.name == name && !step.done) else {
+            log::warn!("Unknown or already finished boot step '{}'", name);
+            return Ok(());
+        };
+
+        step.done = true;
+        log::info!("Boot step '{}' done", name);
+
+        self.set_progress(self.step_progress());
+
+        match self.steps.iter().find(|step| !step.done) {
+            Some(next) => {
+                let message = format!("{}...", next.name);
+                self.set_message(&message);
+            },
+            None => {
+                self.set_message("Ready");
+                self.event_queue.push(Event::BootComplete);
+            },
+        }
+
+        self.draw()
+    }
+
+    /// Shows a 1bpp bitmap centred above the title, pushing the text and progress bar down
+    /// under it. A logo that would leave them no room, or whose data doesn't match its size,
+    /// is left out with a warning rather than drawn over them.
+    pub fn with_logo(mut self, data: &'static [u8], width: u32, height: u32) -> Self {
+        let bytes_per_row = width.div_ceil(8) as usize;
+        if width == 0 || data.len() != bytes_per_row * height as usize {
+            log::warn!("Loading logo is {} bytes, expected {} for {}x{}, skipping it", data.len(), bytes_per_row * height as usize, width, height);
+            return self;
+        }
+
+        //title, message and bar stacked as tightly as they'll go under the logo
+        let (_, title_height) = font_metrics(TextSize::Normal);
+        let (_, message_height) = font_metrics(TextSize::Small);
+        let title_y = LOGO_TOP + height as i32 + ROW_GAP;
+        let message_y = title_y + title_height as i32 + ROW_GAP;
+        let bar_y = message_y + message_height as i32 + ROW_GAP;
+        let bar_bottom = bar_y + self.progress_bar.get_bounds().height as i32;
+
+        if bar_bottom > self.display.height() as i32 {
+            log::warn!("Loading logo is {}px tall, too tall to fit above the text, skipping it", height);
+            return self;
+        }
+
+        self.title.set_position(self.title.get_bounds().x, title_y);
+        self.message.set_position(self.message.get_bounds().x, message_y);
+        self.progress_bar.set_position(self.progress_bar.get_bounds().x, bar_y);
+
+        self.logo = Some(Logo {
+            data,
+            x: (self.display.width() as i32 - width as i32) / 2,
+            width,
+        });
+        self
+    }
+
     pub fn set_message(&mut self, message: &str) {
         self.message.set_text(message);
     }
@@ -36,17 +128,24 @@ impl LoadingScreen {
         self.progress_bar.set_progress(self.progress);
     }
 
-    pub fn update(&mut self) -> Result<(), DisplayError> {
-        let now = std::time::Instant::now();
+    //share of the total weight that's done, 0 with no steps at all
+    fn step_progress(&self) -> u8 {
+        let total: u32 = self.steps.iter().map(|step| step.weight).sum();
+        let done: u32 = self.steps.iter().filter(|step| step.done).map(|step| step.weight).sum();
 
-        if now.duration_since(self.last_update) >= self.step_duration {
-            if self.progress < 100 {
-                self.progress += 1;
-                self.progress_bar.set_progress(self.progress);
-                self.draw()?;
-            }
+        (done * 100).checked_div(total).unwrap_or(0) as u8
+    }
+
+    //the timed fill is only for a screen without real steps to report
+    pub fn update(&mut self) -> Result<(), DisplayError> {
+        if !self.steps.is_empty() {
+            return Ok(());
+        }
 
-            self.last_update = now;
+        if self.step_timer.should_render() && self.progress < 100 {
+            self.progress += 1;
+            self.progress_bar.set_progress(self.progress);
+            self.draw()?;
         }
 
         Ok(())
@@ -61,6 +160,10 @@ impl Screen for LoadingScreen {
     fn draw(&self) -> Result<(), DisplayError> {
         self.display.clear()?;
 
+        if let Some(logo) = &self.logo {
+            self.display.draw_bitmap(logo.x, LOGO_TOP, logo.width, logo.data)?;
+        }
+
         self.title.draw(&self.display)?;
         self.message.draw(&self.display)?;
         self.progress_bar.draw(&self.display)?;
@@ -68,6 +171,12 @@ impl Screen for LoadingScreen {
         self.display.flush()
     }
 
+    fn on_enter(&mut self, _context: &mut ScreenContext) {
+        //steps finished before the screen came up still count
+        self.set_progress(self.step_progress());
+        self.step_timer.reset();
+    }
+
     fn handle_event(&mut self, event: &Event) -> bool {
         match event {
             Event::SystemTick => {
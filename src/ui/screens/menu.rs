@@ -1,11 +1,14 @@
-use crate::drivers::display::{DisplayManager, DisplayError, TextSize};
+use crate::drivers::display::{Canvas, DisplayBackend, DisplayError, TextSize};
 use crate::ui::framework::{Button, Label, Screen, Widget};
-use crate::system::events::Event;
-use std::sync::Arc;
+use crate::system::events::{Event, EventQueue};
+use crate::system::scheduler::Scheduler;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 pub struct MenuItem {
     button: Button,
     action: Arc<dyn Fn() + Send + Sync>,
+    delay: Option<Duration>,
 }
 
 impl MenuItem {
@@ -21,6 +24,7 @@ impl MenuItem {
         Self {
             button,
             action,
+            delay: None,
         }
     }
 }
@@ -29,23 +33,28 @@ pub struct MenuScreen {
     title: Label,
     items: Vec<MenuItem>,
     back_button: Button,
-    display: Arc<DisplayManager>,
+    display: Arc<dyn DisplayBackend>,
+    event_queue: Arc<EventQueue>,
+    scheduler: Option<Arc<Mutex<Scheduler>>>,
+    //items.len() is the back button
     selected_index: usize,
 }
 
 impl MenuScreen {
-    pub fn new(display: Arc<DisplayManager>, title: &str) -> Self {
-        let mut back_button = Button::new("Back", 5, 50, 40, 15);
+    pub fn new(display: Arc<dyn DisplayBackend>, event_queue: Arc<EventQueue>, title: &str) -> Self {
+        let back_y = display.height() as i32 - 15;
+        let mut back_button = Button::new("Back", 5, back_y, 40, 15);
 
-        back_button.set_on_click(|| {
-            log::info!("Back button clicked");
-        });
+        let back_queue = event_queue.clone();
+        back_button.set_on_click(move || back_queue.push(Event::NavigateBack));
 
         Self {
             title: Label::new(title, 5, 5, TextSize::Normal),
             items: Vec::new(),
             back_button,
             display,
+            event_queue,
+            scheduler: None,
             selected_index: 0,
         }
     }
@@ -59,29 +68,56 @@ impl MenuScreen {
         self.items.push(item);
     }
 
-    pub fn select_next(&mut self) {
-        if !self.items.is_empty() {
-            self.selected_index = (self.selected_index + 1) % self.items.len();
-            let _ = self.draw();
+    //needed for add_delayed_item, the scheduler is shared with the main loop that runs it
+    pub fn set_scheduler(&mut self, scheduler: Arc<Mutex<Scheduler>>) {
+        self.scheduler = Some(scheduler);
+    }
+
+    //the action runs on the scheduler after the delay instead of straight away
+    pub fn add_delayed_item<F>(&mut self, text: &str, delay: Duration, action: F)
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        self.add_item(text, action);
+        if let Some(item) = self.items.last_mut() {
+            item.delay = Some(delay);
         }
     }
 
+    pub fn select_next(&mut self) {
+        self.selected_index = (self.selected_index + 1) % (self.items.len() + 1);
+        let _ = self.draw();
+    }
+
     pub fn select_prev(&mut self) {
-        if !self.items.is_empty() {
-            self.selected_index = if self.selected_index == 0 {
-                self.items.len() - 1
-            } else {
-                self.selected_index - 1
-            };
-            let _ = self.draw();
-        }
+        self.selected_index = if self.selected_index == 0 {
+            self.items.len()
+        } else {
+            self.selected_index - 1
+        };
+        let _ = self.draw();
     }
 
+    //the index is the item's position in add_item order, items are never reordered so it's stable
     pub fn activate_selected(&mut self) {
-        if !self.items.is_empty() {
-            let action = &self.items[self.selected_index].action;
-            action();
+        let Some(item) = self.items.get(self.selected_index) else {
+            self.event_queue.push(Event::NavigateBack);
+            return;
+        };
+
+        match (item.delay, &self.scheduler) {
+            (Some(delay), Some(scheduler)) => {
+                let action = Arc::clone(&item.action);
+                scheduler.lock().unwrap().schedule_once(delay, move || action());
+            },
+            (Some(_), None) => {
+                log::warn!("Menu item has a delay but no scheduler is set, running it now");
+                (item.action)();
+            },
+            (None, _) => (item.action)(),
         }
+
+        self.event_queue.push(Event::MenuSelected(self.selected_index));
     }
 }
 
@@ -90,6 +126,7 @@ impl Screen for MenuScreen {
         self.display.clear()?;
 
         self.title.draw(&self.display)?;
+        self.display.draw_hline(0, 16, self.display.width())?;
 
         for (index, item) in self.items.iter().enumerate() {
             
@@ -101,6 +138,11 @@ impl Screen for MenuScreen {
             item.button.draw(&self.display)?;
         }
 
+        if self.selected_index == self.items.len() {
+            let bounds = self.back_button.get_bounds();
+            self.display.draw_rectangle(bounds.x - 2, bounds.y - 2, bounds.width + 4, bounds.height + 4, false)?;
+        }
+
         self.back_button.draw(&self.display)?;
         
         self.display.flush()
@@ -108,15 +150,45 @@ impl Screen for MenuScreen {
 
     fn handle_event(&mut self, event: &Event) -> bool {
         match event {
-            Event::ButtonPressed(pin) if *pin == 32 => {
+            Event::Select => {
                 self.activate_selected();
                 true
             },
-            Event::ButtonPressed(pin) if *pin == 33 => {
+            Event::Down => {
                 self.select_next();
                 true
             },
+            Event::Up => {
+                self.select_prev();
+                true
+            },
             _ => false,
         }
     }
 }
+
+#[cfg(all(test, feature = "simulator"))]
+mod tests {
+    use super::*;
+    use crate::drivers::mock::MockDisplay;
+
+    fn menu(display: Arc<MockDisplay>) -> MenuScreen {
+        let mut menu = MenuScreen::new(display, Arc::new(EventQueue::new()), "Menu");
+        menu.add_item("First", || {});
+        menu.add_item("Second", || {});
+        menu
+    }
+
+    #[test]
+    fn selecting_an_item_redraws_once() {
+        let display = Arc::new(MockDisplay::new(128, 64));
+        let mut menu = menu(display.clone());
+
+        assert!(menu.handle_event(&Event::Down));
+        assert_eq!(display.redraw_count(), 1);
+
+        display.take_calls();
+        assert!(menu.handle_event(&Event::Up));
+        assert_eq!(display.redraw_count(), 1);
+    }
+}
@@ -1,3 +1,30 @@
 pub mod loading;
 pub mod home;
 pub mod menu;
+#[cfg(target_os = "espidf")]
+pub mod settings;
+pub mod icon_grid;
+pub mod error;
+#[cfg(target_os = "espidf")]
+pub mod system_info;
+pub mod keyboard;
+
+//keys the screens are registered under, so navigation doesn't depend on the order they were added
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ScreenId {
+    Loading,
+    Home,
+    Settings,
+    SystemInfo,
+}
+
+impl AsRef<str> for ScreenId {
+    fn as_ref(&self) -> &str {
+        match self {
+            ScreenId::Loading => "loading",
+            ScreenId::Home => "home",
+            ScreenId::Settings => "settings",
+            ScreenId::SystemInfo => "system_info",
+        }
+    }
+}
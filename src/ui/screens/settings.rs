@@ -0,0 +1,187 @@
+use crate::drivers::display::{Canvas, DisplayBackend, DisplayError, TextSize};
+use crate::ui::framework::{Label, Screen, Widget};
+use crate::system::events::{Event, EventQueue};
+use crate::system::settings::SettingsStore;
+use ssd1306::prelude::DisplayRotation;
+use std::sync::{Arc, Mutex};
+
+const CONTRAST_STEPS: [u8; 5] = [0x10, 0x40, 0x7F, 0xCF, 0xFF];
+const TICK_STEPS_MS: [u32; 4] = [10, 50, 100, 250];
+
+const ROW_ROTATION: usize = 0;
+const ROW_CONTRAST: usize = 1;
+const ROW_TICK: usize = 2;
+const ROW_BACK: usize = 3;
+
+pub struct SettingsScreen {
+    title: Label,
+    rows: Vec<Label>,
+    display: Arc<dyn DisplayBackend>,
+    event_queue: Arc<EventQueue>,
+    store: Option<Arc<Mutex<SettingsStore>>>,
+    selected_index: usize,
+    flipped: bool,
+    contrast: u8,
+    tick_ms: u32,
+    on_tick_change: Option<Box<dyn Fn(u32) + Send>>,
+}
+
+impl SettingsScreen {
+    pub fn new(display: Arc<dyn DisplayBackend>, event_queue: Arc<EventQueue>, store: Option<Arc<Mutex<SettingsStore>>>) -> Self {
+        let rows = (0..4)
+            .map(|index| Label::new("", 8, 16 + index * 12, TextSize::Normal))
+            .collect();
+
+        let mut screen = Self {
+            title: Label::new("Settings", 5, 2, TextSize::Normal),
+            rows,
+            display,
+            event_queue,
+            store,
+            selected_index: 0,
+            flipped: true,
+            contrast: 0x7F,
+            tick_ms: 100,
+            on_tick_change: None,
+        };
+
+        screen.load();
+        screen.refresh_rows();
+        screen
+    }
+
+    pub fn tick_interval_ms(&self) -> u32 {
+        self.tick_ms
+    }
+
+    //called with the new interval whenever it's changed here, so the tick source can follow it
+    pub fn set_on_tick_change<F>(&mut self, callback: F)
+    where
+        F: Fn(u32) + Send + 'static,
+    {
+        self.on_tick_change = Some(Box::new(callback));
+    }
+
+    fn load(&mut self) {
+        if let Some(store) = &self.store {
+            let store = store.lock().unwrap();
+
+            self.flipped = store.get_u32("rotation", 1).unwrap_or(1) != 0;
+            self.contrast = store.get_u32("contrast", 0x7F).unwrap_or(0x7F).min(255) as u8;
+            self.tick_ms = store.get_u32("tick_ms", 100).unwrap_or(100);
+        }
+
+        //stored values take effect at boot, not just when changed from this screen
+        self.apply_rotation();
+        let _ = self.display.set_contrast(self.contrast);
+    }
+
+    fn save(&self, key: &str, value: u32) {
+        if let Some(store) = &self.store {
+            let mut store = store.lock().unwrap();
+
+            if let Err(e) = store.set_u32(key, value).and_then(|_| store.commit()) {
+                log::error!("Failed to save setting {}: {}", key, e);
+            }
+        }
+    }
+
+    fn apply_rotation(&self) {
+        //only 0/180 are offered, every screen layout assumes a landscape panel
+        let rotation = if self.flipped { DisplayRotation::Rotate180 } else { DisplayRotation::Rotate0 };
+
+        if let Err(e) = self.display.set_rotation(rotation) {
+            log::error!("Failed to set rotation: {}", e);
+        }
+    }
+
+    fn refresh_rows(&mut self) {
+        let rotation = if self.flipped { "180" } else { "0" };
+        let contrast = self.contrast as u32 * 100 / 255;
+
+        self.rows[ROW_ROTATION].set_text(&format!("Rotation: {}", rotation));
+        self.rows[ROW_CONTRAST].set_text(&format!("Contrast: {}%", contrast));
+        self.rows[ROW_TICK].set_text(&format!("Tick: {}ms", self.tick_ms));
+        self.rows[ROW_BACK].set_text("Back");
+    }
+
+    pub fn select_next(&mut self) {
+        self.selected_index = (self.selected_index + 1) % self.rows.len();
+        let _ = self.draw();
+    }
+
+    pub fn activate_selected(&mut self) {
+        match self.selected_index {
+            ROW_ROTATION => {
+                self.flipped = !self.flipped;
+                self.apply_rotation();
+                self.save("rotation", self.flipped as u32);
+            },
+            ROW_CONTRAST => {
+                let next = CONTRAST_STEPS.iter()
+                    .position(|step| *step > self.contrast)
+                    .unwrap_or(0);
+                self.contrast = CONTRAST_STEPS[next];
+
+                if let Err(e) = self.display.set_contrast(self.contrast) {
+                    log::error!("Failed to set contrast: {}", e);
+                }
+                self.save("contrast", self.contrast as u32);
+            },
+            ROW_TICK => {
+                let next = TICK_STEPS_MS.iter()
+                    .position(|step| *step > self.tick_ms)
+                    .unwrap_or(0);
+                self.tick_ms = TICK_STEPS_MS[next];
+                self.save("tick_ms", self.tick_ms);
+
+                if let Some(callback) = &self.on_tick_change {
+                    callback(self.tick_ms);
+                }
+            },
+            ROW_BACK => {
+                self.event_queue.push(Event::NavigateBack);
+                return;
+            },
+            _ => return,
+        }
+
+        self.refresh_rows();
+        let _ = self.draw();
+    }
+}
+
+impl Screen for SettingsScreen {
+    fn draw(&self) -> Result<(), DisplayError> {
+        self.display.clear()?;
+
+        self.title.draw(&self.display)?;
+
+        for (index, row) in self.rows.iter().enumerate() {
+
+            //same highlight as MenuScreen
+            if index == self.selected_index {
+                let bounds = row.get_bounds();
+                self.display.draw_rectangle(5, bounds.y - 1, 118, bounds.height + 2, false)?;
+            }
+
+            row.draw(&self.display)?;
+        }
+
+        self.display.flush()
+    }
+
+    fn handle_event(&mut self, event: &Event) -> bool {
+        match event {
+            Event::Down => {
+                self.select_next();
+                true
+            },
+            Event::Select => {
+                self.activate_selected();
+                true
+            },
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use crate::drivers::display::{font_metrics, Canvas, DisplayBackend, DisplayError, TextSize};
+use crate::ui::framework::{Label, Screen, ScreenContext, Widget};
+use crate::system::events::{Event, EventQueue};
+use crate::system::clock::SystemClock;
+use std::sync::Arc;
+
+const LEFT: i32 = 4;
+const TITLE_Y: i32 = 2;
+const FIRST_ROW_Y: i32 = 16;
+
+//read-only runtime stats, Back (unclaimed) takes you out again
+pub struct SystemInfoScreen {
+    title: Label,
+    heap: Label,
+    uptime: Label,
+    queue: Label,
+    version: Label,
+    refresh_timer: Option<u32>,
+    system_clock: Arc<SystemClock>,
+    display: Arc<dyn DisplayBackend>,
+    event_queue: Arc<EventQueue>,
+}
+
+impl SystemInfoScreen {
+    pub fn new(display: Arc<dyn DisplayBackend>, event_queue: Arc<EventQueue>, system_clock: Arc<SystemClock>) -> Self {
+        let row_height = font_metrics(TextSize::Small).1 as i32 + 2;
+        let row = |n: i32| FIRST_ROW_Y + n * row_height;
+
+        let mut screen = Self {
+            title: Label::new("System Info", LEFT, TITLE_Y, TextSize::Normal),
+            heap: Label::new("", LEFT, row(0), TextSize::Small),
+            uptime: Label::new("", LEFT, row(1), TextSize::Small),
+            queue: Label::new("", LEFT, row(2), TextSize::Small),
+            version: Label::new(&format!("Firmware v{}", env!("CARGO_PKG_VERSION")), LEFT, row(3), TextSize::Small),
+            refresh_timer: None,
+            system_clock,
+            display,
+            event_queue,
+        };
+
+        screen.refresh_stats();
+        screen
+    }
+
+    //stats only refresh when Timer events with this id arrive, a 1s timer is plenty
+    pub fn set_refresh_timer(&mut self, timer_id: u32) {
+        self.refresh_timer = Some(timer_id);
+    }
+
+    fn refresh_stats(&mut self) {
+        //both counters are maintained by the idf allocator, reading them is just a load
+        let (free, low_water) = unsafe {
+            (esp_idf_sys::esp_get_free_heap_size(), esp_idf_sys::esp_get_minimum_free_heap_size())
+        };
+
+        self.heap.set_text(&format!("Heap {}K (min {}K)", free / 1024, low_water / 1024));
+        self.uptime.set_text(&format!("Up {}", self.system_clock.format_uptime()));
+        let pending = self.event_queue.queue_len();
+        match self.event_queue.capacity() {
+            Some(capacity) => self.queue.set_text(&format!("Queue {}/{} events", pending, capacity)),
+            None => self.queue.set_text(&format!("Queue {} events", pending)),
+        }
+    }
+
+    fn redraw_stats(&self) -> Result<(), DisplayError> {
+        //the values change width as they update, so wipe whole rows rather than the label bounds
+        let width = self.display.width();
+
+        for label in [&self.heap, &self.uptime, &self.queue] {
+            let bounds = label.get_bounds();
+            self.display.clear_region(0, bounds.y, width, bounds.height)?;
+            label.draw(&self.display)?;
+        }
+
+        self.display.flush_dirty()
+    }
+}
+
+impl Screen for SystemInfoScreen {
+    fn draw(&self) -> Result<(), DisplayError> {
+        self.display.clear()?;
+
+        self.title.draw(&self.display)?;
+        self.display.draw_hline(0, FIRST_ROW_Y - 3, self.display.width())?;
+        self.heap.draw(&self.display)?;
+        self.uptime.draw(&self.display)?;
+        self.queue.draw(&self.display)?;
+        self.version.draw(&self.display)?;
+
+        self.display.flush()
+    }
+
+    fn handle_event(&mut self, event: &Event) -> bool {
+        match event {
+            Event::Timer(id) if Some(*id) == self.refresh_timer => {
+                self.refresh_stats();
+                let _ = self.redraw_stats();
+                true
+            },
+            _ => false,
+        }
+    }
+
+    fn on_enter(&mut self, _context: &mut ScreenContext) {
+        //don't show whatever was current when the screen was last left
+        self.refresh_stats();
+    }
+}
@@ -0,0 +1,148 @@
+use crate::drivers::display::{Canvas, DisplayBackend, DisplayError};
+use crate::system::events::Event;
+use crate::system::time::FrameTimer;
+use crate::ui::animations::Animation;
+use crate::ui::sprite::{Sprite, SpriteAnimation};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+//bounces a sprite around the panel once there's been no input for the timeout
+pub struct Screensaver {
+    display: Arc<dyn DisplayBackend>,
+    sprite: Sprite,
+    animation: SpriteAnimation,
+    velocity: (i32, i32),
+    timeout: Duration,
+    last_activity: Instant,
+    last_update: Instant,
+    frame_timer: FrameTimer,
+    active: bool,
+    dim_level: Option<u8>,
+    restore_level: Option<u8>,
+}
+
+impl Screensaver {
+    pub fn new(display: Arc<dyn DisplayBackend>, sprite: Sprite, animation: SpriteAnimation, timeout: Duration) -> Self {
+        Self {
+            display,
+            sprite,
+            animation,
+            velocity: (1, 1),
+            timeout,
+            last_activity: Instant::now(),
+            last_update: Instant::now(),
+            frame_timer: FrameTimer::new(20),
+            active: false,
+            dim_level: None,
+            restore_level: None,
+        }
+    }
+
+    /// Drops the contrast to `dim_level` while the screensaver runs, on top of whatever an
+    /// `IdleDimmer` already did, and puts back the level it found when it's dismissed.
+    pub fn with_dim_level(mut self, dim_level: u8) -> Self {
+        self.dim_level = Some(dim_level);
+        self
+    }
+
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    //returns true when the input only woke the screensaver and shouldn't reach the screen
+    pub fn on_event(&mut self, event: &Event) -> bool {
+        if !event.is_user_input() {
+            return false;
+        }
+
+        self.last_activity = Instant::now();
+
+        if self.active {
+            self.active = false;
+            self.restore_contrast();
+            return true;
+        }
+
+        false
+    }
+
+    pub fn update(&mut self) -> Result<(), DisplayError> {
+        if !self.active {
+            if self.last_activity.elapsed() < self.timeout {
+                return Ok(());
+            }
+
+            self.active = true;
+            self.dim();
+            self.animation.reset();
+            self.last_update = Instant::now();
+            self.frame_timer.reset();
+        }
+
+        if !self.frame_timer.should_render() {
+            return Ok(());
+        }
+
+        let now = Instant::now();
+        self.animation.update(now.duration_since(self.last_update));
+        self.last_update = now;
+        self.sprite.set_frame(self.animation.current_frame());
+
+        self.bounce();
+        self.redraw()
+    }
+
+    //paints the current frame again without advancing it, for when something else drew over it
+    pub fn redraw(&self) -> Result<(), DisplayError> {
+        self.display.draw_frame(&mut |ctx| {
+            ctx.clear()?;
+            self.sprite.draw(ctx)
+        })
+    }
+
+    fn dim(&mut self) {
+        let Some(dim_level) = self.dim_level else {
+            return;
+        };
+
+        let current = self.display.contrast();
+        match self.display.set_contrast(dim_level.min(current)) {
+            Ok(_) => self.restore_level = Some(current),
+            Err(e) => log::error!("Failed to dim display for the screensaver: {}", e),
+        }
+    }
+
+    fn restore_contrast(&mut self) {
+        if let Some(level) = self.restore_level.take() {
+            if let Err(e) = self.display.set_contrast(level) {
+                log::error!("Failed to restore contrast: {}", e);
+            }
+        }
+    }
+
+    fn bounce(&mut self) {
+        let (x, y) = self.sprite.position();
+        let (width, height) = self.sprite.size();
+        let max_x = self.display.width() as i32 - width as i32;
+        let max_y = self.display.height() as i32 - height as i32;
+
+        let mut next_x = x + self.velocity.0;
+        let mut next_y = y + self.velocity.1;
+
+        if next_x < 0 || next_x > max_x {
+            self.velocity.0 = -self.velocity.0;
+            next_x = next_x.clamp(0, max_x.max(0));
+        }
+
+        if next_y < 0 || next_y > max_y {
+            self.velocity.1 = -self.velocity.1;
+            next_y = next_y.clamp(0, max_y.max(0));
+        }
+
+        self.sprite.set_position(next_x, next_y);
+    }
+}
@@ -0,0 +1,198 @@
+use crate::drivers::display::{Canvas, DisplayError};
+use crate::ui::animations::{Animation, AnimationState, RepeatMode};
+use crate::system::time::frame_duration_for;
+use std::time::Duration;
+
+//frames are copied into owned buffers, so a sprite costs frame_bytes() * frame_count() of heap
+pub struct Sprite {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    frames: Vec<Vec<u8>>,
+    current_frame: usize,
+}
+
+impl Sprite {
+    pub fn new(x: i32, y: i32, width: u32, height: u32, frames: &[&[u8]]) -> Result<Self, DisplayError> {
+        let frame_bytes = (width.div_ceil(8) * height) as usize;
+
+        //every frame has to be a full 1bpp image of the same size
+        if width == 0 || height == 0 || frames.is_empty() || frames.iter().any(|frame| frame.len() != frame_bytes) {
+            return Err(DisplayError::DrawError);
+        }
+
+        Ok(Self {
+            x,
+            y,
+            width,
+            height,
+            frames: frames.iter().map(|frame| frame.to_vec()).collect(),
+            current_frame: 0,
+        })
+    }
+
+    pub fn advance_frame(&mut self) {
+        self.current_frame = (self.current_frame + 1) % self.frames.len();
+    }
+
+    pub fn set_frame(&mut self, index: usize) {
+        self.current_frame = index % self.frames.len();
+    }
+
+    pub fn current_frame(&self) -> usize {
+        self.current_frame
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn frame_bytes(&self) -> usize {
+        self.frames[0].len()
+    }
+
+    pub fn memory_usage(&self) -> usize {
+        self.frame_bytes() * self.frame_count()
+    }
+
+    pub fn set_position(&mut self, x: i32, y: i32) {
+        self.x = x;
+        self.y = y;
+    }
+
+    pub fn position(&self) -> (i32, i32) {
+        (self.x, self.y)
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    pub fn draw(&self, display: &dyn Canvas) -> Result<(), DisplayError> {
+        display.draw_bitmap(self.x, self.y, self.width, &self.frames[self.current_frame])
+    }
+}
+
+pub struct SpriteAnimation {
+    frame_count: usize,
+    frame_duration: Duration,
+    elapsed: Duration,
+    current_frame: usize,
+    state: AnimationState,
+    changed: bool,
+    repeat: RepeatMode,
+    reversed: bool,
+}
+
+impl SpriteAnimation {
+    pub fn new(frame_count: usize, fps: u32) -> Self {
+        Self {
+            frame_count: frame_count.max(1),
+            frame_duration: frame_duration_for(fps),
+            elapsed: Duration::from_secs(0),
+            current_frame: 0,
+            state: AnimationState::Ready,
+            changed: false,
+            repeat: RepeatMode::Loop,
+            reversed: false,
+        }
+    }
+
+    pub fn with_repeat(mut self, repeat: RepeatMode) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    pub fn set_fps(&mut self, fps: u32) {
+        self.frame_duration = frame_duration_for(fps);
+    }
+
+    pub fn current_frame(&self) -> usize {
+        self.current_frame
+    }
+
+    //returns true once a Once animation has shown its last frame
+    fn step(&mut self) -> bool {
+        let last = self.frame_count - 1;
+
+        match self.repeat {
+            RepeatMode::Once => {
+                if self.current_frame == last {
+                    return true;
+                }
+                self.current_frame += 1;
+                false
+            },
+            RepeatMode::Loop => {
+                self.current_frame = (self.current_frame + 1) % self.frame_count;
+                false
+            },
+            RepeatMode::PingPong => {
+                if last == 0 {
+                    return false;
+                }
+                if self.current_frame == last {
+                    self.reversed = true;
+                } else if self.current_frame == 0 {
+                    self.reversed = false;
+                }
+                self.current_frame = if self.reversed { self.current_frame - 1 } else { self.current_frame + 1 };
+                false
+            },
+        }
+    }
+}
+
+impl Animation for SpriteAnimation {
+    fn update(&mut self, delta_time: Duration) -> bool {
+        let before = self.current_frame;
+
+        let done = match self.state {
+            AnimationState::Ready => {
+                self.state = AnimationState::Running;
+                self.elapsed = Duration::from_secs(0);
+                self.current_frame = 0;
+                false
+            },
+            AnimationState::Running => {
+                self.elapsed += delta_time;
+
+                //a long gap can cover several frames, step through all of them
+                while self.elapsed >= self.frame_duration {
+                    self.elapsed -= self.frame_duration;
+
+                    if self.step() {
+                        self.state = AnimationState::Completed;
+                        break;
+                    }
+                }
+
+                matches!(self.state, AnimationState::Completed)
+            },
+            AnimationState::Completed => true,
+        };
+
+        self.changed = self.current_frame != before;
+        done
+    }
+
+    fn reset(&mut self) {
+        self.state = AnimationState::Ready;
+        self.elapsed = Duration::from_secs(0);
+        self.current_frame = 0;
+        self.reversed = false;
+    }
+
+    fn get_state(&self) -> AnimationState {
+        self.state
+    }
+
+    fn value_changed(&self) -> bool {
+        self.changed
+    }
+
+    fn is_looping(&self) -> bool {
+        self.repeat != RepeatMode::Once
+    }
+}
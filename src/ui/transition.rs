@@ -0,0 +1,137 @@
+use crate::drivers::display::{DisplayBackend, DisplayError};
+use crate::ui::animations::{Animation, Easing, FadeAnimation, SlideAnimation};
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_TRANSITION_DURATION: Duration = Duration::from_millis(250);
+
+//4x4 ordered dither, a pixel flips to the new screen once progress passes its threshold
+const BAYER_4X4: [[u8; 4]; 4] = [
+    [0, 8, 2, 10],
+    [12, 4, 14, 6],
+    [3, 11, 1, 9],
+    [15, 7, 13, 5],
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Transition {
+    None,
+    //named for the way the outgoing screen moves, the new one follows it in from the other side
+    SlideLeft,
+    SlideRight,
+    SlideUp,
+    SlideDown,
+    //monochrome stand-in for a fade, pixels swap over in a dither pattern
+    Dissolve,
+}
+
+enum Driver {
+    //the animation moves the outgoing frame, the incoming one sits a screen away from it
+    Slide { animation: SlideAnimation, incoming_offset: (i32, i32) },
+    Dissolve(FadeAnimation),
+}
+
+pub(crate) struct ScreenTransition {
+    driver: Driver,
+    outgoing: Vec<u8>,
+    incoming: Vec<u8>,
+    width: u32,
+    height: u32,
+    last_step: Instant,
+}
+
+impl ScreenTransition {
+    //None for Transition::None, or frames that don't match the panel
+    pub(crate) fn new(kind: Transition, outgoing: Vec<u8>, incoming: Vec<u8>, width: u32, height: u32, duration: Duration) -> Option<Self> {
+        let frame_len = width.div_ceil(8) as usize * height as usize;
+        if outgoing.len() != frame_len || incoming.len() != frame_len {
+            return None;
+        }
+
+        let (w, h) = (width as i32, height as i32);
+
+        let slide = |end: (i32, i32)| Driver::Slide {
+            animation: SlideAnimation::new((0, 0), end, duration).with_easing(Easing::EaseOutQuad),
+            incoming_offset: (-end.0, -end.1),
+        };
+
+        let driver = match kind {
+            Transition::None => return None,
+            Transition::SlideLeft => slide((-w, 0)),
+            Transition::SlideRight => slide((w, 0)),
+            Transition::SlideUp => slide((0, -h)),
+            Transition::SlideDown => slide((0, h)),
+            Transition::Dissolve => Driver::Dissolve(FadeAnimation::new(0.0, 1.0, duration)),
+        };
+
+        Some(Self {
+            driver,
+            outgoing,
+            incoming,
+            width,
+            height,
+            last_step: Instant::now(),
+        })
+    }
+
+    //draws the next frame and flushes it, false once the incoming screen is fully in place
+    pub(crate) fn step(&mut self, display: &dyn DisplayBackend) -> Result<bool, DisplayError> {
+        let now = Instant::now();
+        let delta = now.duration_since(self.last_step);
+        self.last_step = now;
+
+        let done = match &mut self.driver {
+            Driver::Slide { animation, .. } => animation.update(delta),
+            Driver::Dissolve(animation) => animation.update(delta),
+        };
+
+        if done {
+            return Ok(false);
+        }
+
+        let changed = match &self.driver {
+            Driver::Slide { animation, .. } => animation.value_changed(),
+            Driver::Dissolve(animation) => animation.value_changed(),
+        };
+
+        //same frame as last time, leave the bus alone
+        if !changed {
+            return Ok(true);
+        }
+
+        match &self.driver {
+            Driver::Slide { animation, incoming_offset } => {
+                //the two frames together always cover the panel, render_bitmap clips whatever hangs off
+                let (x, y) = animation.get_position();
+                display.draw_bitmap(x, y, self.width, &self.outgoing)?;
+                display.draw_bitmap(x + incoming_offset.0, y + incoming_offset.1, self.width, &self.incoming)?;
+            },
+            Driver::Dissolve(animation) => {
+                let frame = self.dissolve(animation.get_value());
+                display.draw_bitmap(0, 0, self.width, &frame)?;
+            },
+        }
+
+        display.flush_dirty()?;
+        Ok(true)
+    }
+
+    fn dissolve(&self, progress: f32) -> Vec<u8> {
+        let threshold = (progress * 16.0) as u8;
+        let bytes_per_row = self.width.div_ceil(8) as usize;
+        let mut frame = self.outgoing.clone();
+
+        for y in 0..self.height as usize {
+            for x in 0..self.width as usize {
+                if BAYER_4X4[y % 4][x % 4] >= threshold {
+                    continue;
+                }
+
+                let byte = y * bytes_per_row + x / 8;
+                let mask = 0x80 >> (x % 8);
+                frame[byte] = (frame[byte] & !mask) | (self.incoming[byte] & mask);
+            }
+        }
+
+        frame
+    }
+}